@@ -0,0 +1,210 @@
+use crate::{export::Exported, strength::Estimator};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::{
+    collections::HashMap,
+    fmt::{self, Display, Formatter},
+};
+
+/// Prefix of the secrets stored with `kb otp insert`, they aren't passwords.
+const OTP_PREFIX: &str = "otpauth://";
+
+/// What's wrong with a password.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Problem {
+    /// Other entries have the same password.
+    Reused,
+    /// The password is easy to guess.
+    Weak,
+    /// Another entry has almost the same password.
+    Similar,
+    /// There's no website to know where the password is used.
+    NoWebsite,
+}
+
+impl Display for Problem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Problem::Reused => "reused",
+            Problem::Weak => "weak",
+            Problem::Similar => "similar",
+            Problem::NoWebsite => "no-website",
+        })
+    }
+}
+
+/// A problem with a password, it never contains the password itself.
+///
+/// As TSV the columns are `problem`, `id`, `name` and `detail`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Finding {
+    /// What's wrong.
+    pub problem: Problem,
+    /// ID of the password.
+    pub id: String,
+    /// Name of the password.
+    pub name: String,
+    /// Explanation of the problem.
+    pub detail: String,
+}
+
+/// Check the passwords for problems, sorted by problem and name.
+///
+/// Passwords are reused when their hashes are the same and similar when a few edits turn one
+/// into the other. Passwords with a strength score below `min_score` are weak.
+pub fn audit(passwords: &[Exported], estimator: &Estimator, min_score: u8) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let mut finding = |password: &Exported, problem: Problem, detail: String| {
+        findings.push(Finding {
+            problem,
+            id: password.id.clone(),
+            name: password.name.clone(),
+            detail,
+        })
+    };
+
+    // OTP secrets are long random strings that are checked by the service
+    let checked = passwords
+        .iter()
+        .filter(|password| !password.password.starts_with(OTP_PREFIX))
+        .collect::<Vec<_>>();
+
+    // Group the passwords by their hash
+    let mut hashes = HashMap::<_, Vec<&Exported>>::new();
+    for password in &checked {
+        hashes
+            .entry(Sha256::digest(password.password.as_bytes()))
+            .or_default()
+            .push(password);
+    }
+    for same in hashes.values().filter(|same| same.len() > 1) {
+        for password in same {
+            finding(
+                password,
+                Problem::Reused,
+                format!(
+                    "Same password as {}",
+                    names(same.iter().copied().filter(|other| other.id != password.id))
+                ),
+            );
+        }
+    }
+
+    for password in &checked {
+        let estimate = estimator.estimate(&password.password);
+        if estimate.score < min_score {
+            finding(
+                password,
+                Problem::Weak,
+                format!(
+                    "Strength score {} of 4, cracked in about 10^{:.0} guesses",
+                    estimate.score,
+                    estimate.guesses.log10()
+                ),
+            );
+        }
+
+        let similar = checked
+            .iter()
+            .copied()
+            .filter(|other| similar(&password.password, &other.password))
+            .collect::<Vec<_>>();
+        if !similar.is_empty() {
+            finding(
+                password,
+                Problem::Similar,
+                format!("Almost the same password as {}", names(similar.into_iter())),
+            );
+        }
+    }
+
+    for password in passwords {
+        if password.website.is_none() {
+            finding(password, Problem::NoWebsite, "No website".to_string());
+        }
+    }
+
+    findings.sort_by(|a, b| (a.problem, &a.name).cmp(&(b.problem, &b.name)));
+
+    findings
+}
+
+/// Whether a few edits turn one password into the other, the same password isn't similar.
+fn similar(a: &str, b: &str) -> bool {
+    let (a, b) = (a.chars().collect::<Vec<_>>(), b.chars().collect::<Vec<_>>());
+    // Allow an edit for every 4 characters
+    let max_distance = (a.len().min(b.len()) / 4).max(1);
+
+    a != b && a.len().abs_diff(b.len()) <= max_distance && distance(&a, &b) <= max_distance
+}
+
+/// The Levenshtein distance, the fewest insertions, deletions and substitutions.
+fn distance(a: &[char], b: &[char]) -> usize {
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    for (i, a) in a.iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, b) in b.iter().enumerate() {
+            current[j + 1] = (previous[j] + usize::from(a != b))
+                .min(previous[j + 1] + 1)
+                .min(current[j] + 1);
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+/// The quoted names of the passwords.
+fn names<'a, I>(passwords: I) -> String
+where
+    I: Iterator<Item = &'a Exported>,
+{
+    let mut names = passwords
+        .map(|password| format!("\"{}\"", password.name))
+        .collect::<Vec<_>>();
+    names.sort();
+
+    names.join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Problem;
+    use crate::{export::exported, strength::Estimator};
+
+    #[test]
+    fn audit() {
+        let passwords = [
+            exported("1", "mail", Some("mail.example.com"), "x7#Kp9!qLz2$vB"),
+            exported("2", "forum", Some("forum.example.com"), "x7#Kp9!qLz2$vB"),
+            exported("3", "bank", Some("bank.example.com"), "password1"),
+            exported("4", "shop", None, "Fq8@wN3#zR6!tY"),
+            exported("5", "shop-old", Some("shop.example.com"), "Fq8@wN3#zR6!tX"),
+            exported("6", "github-otp", None, "otpauth://totp/x?secret=AB"),
+        ];
+
+        let findings = super::audit(&passwords, &Estimator::new(), 3)
+            .into_iter()
+            .map(|finding| (finding.problem, finding.name))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            findings,
+            [
+                (Problem::Reused, "forum".to_string()),
+                (Problem::Reused, "mail".to_string()),
+                (Problem::Weak, "bank".to_string()),
+                (Problem::Similar, "shop".to_string()),
+                (Problem::Similar, "shop-old".to_string()),
+                (Problem::NoWebsite, "github-otp".to_string()),
+                (Problem::NoWebsite, "shop".to_string()),
+            ]
+        );
+
+        // The passwords are never part of the findings
+        for finding in super::audit(&passwords, &Estimator::new(), 3) {
+            assert!(!finding.detail.contains("x7#Kp9"));
+            assert!(!finding.detail.contains("password1"));
+        }
+    }
+}
@@ -0,0 +1,264 @@
+use anyhow::{anyhow, ensure, Result};
+use chacha20poly1305::{
+    aead::{Aead, NewAead, Payload},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use std::convert::TryInto;
+
+/// Bytes every backup starts with.
+const MAGIC: &[u8; 8] = b"KBBACKUP";
+/// Version of the format that's written.
+pub const VERSION: u16 = 1;
+/// Size of the random salt of the key derivation.
+const SALT_SIZE: usize = 16;
+/// Size of the random nonce of the cipher.
+const NONCE_SIZE: usize = 12;
+/// Size of the key derived from the passphrase.
+const KEY_SIZE: usize = 32;
+/// Size of the header in bytes.
+const HEADER_SIZE: usize = MAGIC.len() + 2 + 8 + 4 + 4 + 4 + 4 + SALT_SIZE + NONCE_SIZE;
+/// Memory Argon2id uses in KiB.
+const MEMORY: u32 = 64 * 1024;
+/// Number of passes of Argon2id.
+const ITERATIONS: u32 = 3;
+/// Number of lanes of Argon2id.
+const PARALLELISM: u32 = 1;
+
+/// A password in a backup.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupEntry {
+    /// Name of the password.
+    pub name: String,
+    /// Associated e-mail.
+    pub email: Option<String>,
+    /// Associated website.
+    pub website: Option<String>,
+    /// The actual password.
+    pub password: String,
+}
+
+/// The unencrypted start of a backup.
+///
+/// All numbers are big-endian, the layout is described in the README. The whole header is
+/// authenticated as associated data, so it can't be changed without the decryption failing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Header {
+    /// Version of the format.
+    pub version: u16,
+    /// When the backup was created, in seconds since the UNIX epoch.
+    pub created: u64,
+    /// Number of passwords in the backup.
+    pub entries: u32,
+    /// Memory Argon2id uses in KiB.
+    memory: u32,
+    /// Number of passes of Argon2id.
+    iterations: u32,
+    /// Number of lanes of Argon2id.
+    parallelism: u32,
+    /// Salt of the key derivation.
+    salt: [u8; SALT_SIZE],
+    /// Nonce of the cipher.
+    nonce: [u8; NONCE_SIZE],
+}
+
+impl Header {
+    /// Header for a new backup with a random salt and nonce.
+    pub fn new(created: u64, entries: u32) -> Self {
+        let mut salt = [0; SALT_SIZE];
+        OsRng.fill_bytes(&mut salt);
+        let mut nonce = [0; NONCE_SIZE];
+        OsRng.fill_bytes(&mut nonce);
+
+        Self {
+            version: VERSION,
+            created,
+            entries,
+            memory: MEMORY,
+            iterations: ITERATIONS,
+            parallelism: PARALLELISM,
+            salt,
+            nonce,
+        }
+    }
+
+    /// Read the header at the start of a backup, without decrypting anything.
+    pub fn parse(bytes: &[u8]) -> Result<Self> {
+        ensure!(
+            bytes.len() >= HEADER_SIZE && bytes.starts_with(MAGIC),
+            "Not a keybear backup"
+        );
+
+        let mut rest = &bytes[MAGIC.len()..HEADER_SIZE];
+        let mut take = |size: usize| {
+            let (value, after) = rest.split_at(size);
+            rest = after;
+
+            value
+        };
+
+        let version = u16::from_be_bytes(take(2).try_into()?);
+        ensure!(
+            version == VERSION,
+            "Backup format version {} is not supported, only version {} is",
+            version,
+            VERSION
+        );
+
+        let header = Self {
+            version,
+            created: u64::from_be_bytes(take(8).try_into()?),
+            entries: u32::from_be_bytes(take(4).try_into()?),
+            memory: u32::from_be_bytes(take(4).try_into()?),
+            iterations: u32::from_be_bytes(take(4).try_into()?),
+            parallelism: u32::from_be_bytes(take(4).try_into()?),
+            salt: take(SALT_SIZE).try_into()?,
+            nonce: take(NONCE_SIZE).try_into()?,
+        };
+
+        // The header isn't authenticated before the key is derived, so a changed file could
+        // otherwise make the key derivation use any amount of memory and time
+        ensure!(
+            (1..=MEMORY).contains(&header.memory)
+                && (1..=ITERATIONS).contains(&header.iterations)
+                && (1..=PARALLELISM).contains(&header.parallelism),
+            "Key derivation parameters of the backup are not supported, it might be damaged"
+        );
+
+        Ok(header)
+    }
+
+    /// The header as it's written to the file.
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_SIZE);
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&self.version.to_be_bytes());
+        bytes.extend_from_slice(&self.created.to_be_bytes());
+        bytes.extend_from_slice(&self.entries.to_be_bytes());
+        bytes.extend_from_slice(&self.memory.to_be_bytes());
+        bytes.extend_from_slice(&self.iterations.to_be_bytes());
+        bytes.extend_from_slice(&self.parallelism.to_be_bytes());
+        bytes.extend_from_slice(&self.salt);
+        bytes.extend_from_slice(&self.nonce);
+
+        bytes
+    }
+
+    /// Derive the key from the passphrase with Argon2id.
+    fn key(&self, passphrase: &str) -> Result<Key> {
+        let config = argon2::Config {
+            variant: argon2::Variant::Argon2id,
+            version: argon2::Version::Version13,
+            mem_cost: self.memory,
+            time_cost: self.iterations,
+            lanes: self.parallelism,
+            hash_length: KEY_SIZE as u32,
+            ..argon2::Config::default()
+        };
+
+        let key: [u8; KEY_SIZE] = argon2::hash_raw(passphrase.as_bytes(), &self.salt, &config)
+            .map_err(|err| anyhow!("Could not derive key from passphrase: {}", err))?
+            .try_into()
+            .map_err(|_| anyhow!("Derived key has the wrong size"))?;
+
+        Ok(Key::from(key))
+    }
+}
+
+/// Encrypt the passwords into a backup with the header.
+pub fn seal(header: &Header, passphrase: &str, entries: &[BackupEntry]) -> Result<Vec<u8>> {
+    ensure!(
+        header.entries as usize == entries.len(),
+        "Header doesn't match the number of passwords"
+    );
+
+    let mut bytes = header.to_bytes();
+    let plaintext = serde_json::to_vec(entries)?;
+
+    let cipher = ChaCha20Poly1305::new(&header.key(passphrase)?);
+    let encrypted = cipher
+        .encrypt(
+            &Nonce::from(header.nonce),
+            Payload {
+                msg: &plaintext,
+                aad: &bytes,
+            },
+        )
+        .map_err(|_| anyhow!("Could not encrypt backup"))?;
+    bytes.extend_from_slice(&encrypted);
+
+    Ok(bytes)
+}
+
+/// Decrypt a backup, fails when the passphrase is wrong or the file was changed.
+pub fn open(bytes: &[u8], passphrase: &str) -> Result<(Header, Vec<BackupEntry>)> {
+    let header = Header::parse(bytes)?;
+    let (header_bytes, encrypted) = bytes.split_at(HEADER_SIZE);
+
+    let cipher = ChaCha20Poly1305::new(&header.key(passphrase)?);
+    let plaintext = cipher
+        .decrypt(
+            &Nonce::from(header.nonce),
+            Payload {
+                msg: encrypted,
+                aad: header_bytes,
+            },
+        )
+        .map_err(|_| {
+            anyhow!("Could not decrypt backup, the passphrase is wrong or it's damaged")
+        })?;
+
+    let entries: Vec<BackupEntry> = serde_json::from_slice(&plaintext)?;
+    ensure!(
+        entries.len() == header.entries as usize,
+        "Backup contains {} passwords but the header says {}",
+        entries.len(),
+        header.entries
+    );
+
+    Ok((header, entries))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BackupEntry, Header};
+    use anyhow::Result;
+
+    #[test]
+    fn roundtrip() -> Result<()> {
+        let entries = vec![BackupEntry {
+            name: "mail".to_string(),
+            email: Some("me@home.com".to_string()),
+            website: None,
+            password: "hunter2".to_string(),
+        }];
+        // Use little memory to keep the test fast
+        let header = Header {
+            memory: 64,
+            iterations: 1,
+            ..Header::new(1_600_000_000, 1)
+        };
+
+        let bytes = super::seal(&header, "correct horse", &entries)?;
+        assert!(!String::from_utf8_lossy(&bytes).contains("hunter2"));
+
+        // The header can be read without the passphrase
+        assert_eq!(Header::parse(&bytes)?, header);
+
+        assert_eq!(super::open(&bytes, "correct horse")?, (header, entries));
+        assert!(super::open(&bytes, "wrong").is_err());
+
+        // The header is authenticated, change the number of entries
+        let mut changed = bytes.clone();
+        changed[super::MAGIC.len() + 2 + 8 + 3] = 2;
+        assert!(super::open(&changed, "correct horse").is_err());
+
+        // More memory than a backup is written with is refused before deriving the key
+        let mut changed = bytes;
+        changed[super::MAGIC.len() + 2 + 8 + 4] = 0xff;
+        assert!(Header::parse(&changed).is_err());
+
+        Ok(())
+    }
+}
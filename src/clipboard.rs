@@ -0,0 +1,265 @@
+use crate::config::Config;
+use anyhow::{anyhow, bail, ensure, Result};
+use log::debug;
+use serde::{Deserialize, Serialize};
+use std::{
+    env,
+    io::{self, Write},
+    process::{Command, Stdio},
+    thread,
+    time::Duration,
+};
+
+/// Name of the hidden subcommand that clears the clipboard in the background.
+pub const CLEAR_SUBCOMMAND: &str = "clipboard-clear";
+
+/// What's sent to the background process that clears the clipboard.
+#[derive(Debug, Serialize, Deserialize)]
+struct ClearRequest {
+    /// The secret that was copied, the clipboard is only cleared when it still holds it.
+    secret: String,
+    /// What was on the clipboard before, it's restored when clearing.
+    previous: Option<String>,
+}
+
+/// Put the secret on the clipboard and clear it in the background after the configured timeout.
+pub fn copy(config: &Config, secret: &str) -> Result<()> {
+    let (copy_command, paste_command) = commands(config)?;
+
+    // Remember what was on the clipboard so it can be restored, an empty clipboard can fail
+    let previous = paste(&paste_command).ok();
+
+    set(&copy_command, secret)?;
+
+    let timeout = config.clipboard().timeout();
+    if timeout == 0 {
+        return Ok(());
+    }
+
+    debug!(
+        "Spawning process to clear the clipboard in {} seconds",
+        timeout
+    );
+
+    // Spawn a copy of ourselves that will outlive this process
+    let mut command = Command::new(env::current_exe()?);
+    // Detach it from the terminal so closing it or pressing Ctrl+C doesn't kill it
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+    let mut child = command
+        .arg("--config")
+        .arg(config.path())
+        .arg(CLEAR_SUBCOMMAND)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|err| anyhow!("Could not start process to clear the clipboard: {}", err))?;
+
+    // Pass the secret through stdin so it doesn't show up in the process list
+    let stdin = child
+        .stdin
+        .take()
+        .ok_or_else(|| anyhow!("Could not open stdin of clipboard process"))?;
+    serde_json::to_writer(
+        stdin,
+        &ClearRequest {
+            secret: secret.to_string(),
+            previous,
+        },
+    )?;
+
+    Ok(())
+}
+
+/// Wait for the configured timeout and clear the clipboard.
+///
+/// This is invoked by the background process spawned by `copy`.
+pub fn clear(config: &Config) -> Result<()> {
+    let request: ClearRequest = serde_json::from_reader(io::stdin())
+        .map_err(|err| anyhow!("Invalid clipboard clear request: {}", err))?;
+
+    thread::sleep(Duration::from_secs(config.clipboard().timeout()));
+
+    restore(config, &request)
+}
+
+/// Restore the previous contents of the clipboard if it still holds the secret.
+fn restore(config: &Config, request: &ClearRequest) -> Result<()> {
+    let (copy_command, paste_command) = commands(config)?;
+
+    // Don't touch the clipboard when something else was copied in the meantime
+    if paste(&paste_command)?.trim_end_matches('\n') != request.secret {
+        debug!("Clipboard changed, not clearing it");
+
+        return Ok(());
+    }
+
+    debug!("Clearing clipboard");
+
+    set(
+        &copy_command,
+        request.previous.as_deref().unwrap_or_default(),
+    )
+}
+
+/// The configured copy and paste commands, or the ones detected from the environment.
+fn commands(config: &Config) -> Result<(String, String)> {
+    let clipboard = config.clipboard();
+    if let (Some(copy), Some(paste)) = (clipboard.copy(), clipboard.paste()) {
+        return Ok((copy.to_string(), paste.to_string()));
+    }
+
+    let (copy, paste) = if env::var_os("WAYLAND_DISPLAY").is_some() {
+        ("wl-copy", "wl-paste --no-newline")
+    } else if cfg!(target_os = "macos") {
+        ("pbcopy", "pbpaste")
+    } else if env::var_os("DISPLAY").is_some() && !installed("xclip") && installed("xsel") {
+        ("xsel --clipboard --input", "xsel --clipboard --output")
+    } else if env::var_os("DISPLAY").is_some() {
+        (
+            "xclip -selection clipboard",
+            "xclip -selection clipboard -o",
+        )
+    } else {
+        bail!("No clipboard found, set \"copy\" and \"paste\" in the [clipboard] section of the configuration file");
+    };
+
+    Ok((
+        clipboard.copy().unwrap_or(copy).to_string(),
+        clipboard.paste().unwrap_or(paste).to_string(),
+    ))
+}
+
+/// Whether the program is in one of the directories of `PATH`.
+fn installed(program: &str) -> bool {
+    env::var_os("PATH")
+        .is_some_and(|path| env::split_paths(&path).any(|dir| dir.join(program).is_file()))
+}
+
+/// Put the text on the clipboard.
+fn set(copy_command: &str, text: &str) -> Result<()> {
+    let mut child = command(copy_command)?
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn()
+        .map_err(|err| {
+            anyhow!(
+                "Could not run clipboard command \"{}\": {}",
+                copy_command,
+                err
+            )
+        })?;
+
+    child
+        .stdin
+        .take()
+        .ok_or_else(|| anyhow!("Could not open stdin of \"{}\"", copy_command))?
+        .write_all(text.as_bytes())?;
+
+    let status = child.wait()?;
+    ensure!(
+        status.success(),
+        "Clipboard command \"{}\" exited with {}",
+        copy_command,
+        status
+    );
+
+    Ok(())
+}
+
+/// Get the text on the clipboard.
+fn paste(paste_command: &str) -> Result<String> {
+    let output = command(paste_command)?
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .map_err(|err| {
+            anyhow!(
+                "Could not run clipboard command \"{}\": {}",
+                paste_command,
+                err
+            )
+        })?;
+
+    ensure!(
+        output.status.success(),
+        "Clipboard command \"{}\" exited with {}",
+        paste_command,
+        output.status
+    );
+
+    String::from_utf8(output.stdout).map_err(|err| anyhow!("Clipboard is not UTF-8: {}", err))
+}
+
+/// Build a command from a string with the program and its arguments.
+fn command(command: &str) -> Result<Command> {
+    let mut args = command.split_whitespace();
+    let program = args
+        .next()
+        .ok_or_else(|| anyhow!("Clipboard command is empty"))?;
+
+    let mut command = Command::new(program);
+    command.args(args);
+
+    Ok(command)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::ClearRequest;
+    use crate::config::Config;
+    use anyhow::Result;
+    use std::{fs, os::unix::fs::PermissionsExt, str::FromStr};
+
+    #[test]
+    fn copy_and_restore() -> Result<()> {
+        // Stub clipboard commands that use a file as the clipboard
+        let dir = tempfile::tempdir()?;
+        let clipboard = dir.path().join("clipboard");
+        let copy = dir.path().join("copy.sh");
+        let paste = dir.path().join("paste.sh");
+        fs::write(&copy, format!("#!/bin/sh\ncat > {:?}\n", clipboard))?;
+        fs::write(&paste, format!("#!/bin/sh\ncat {:?}\n", clipboard))?;
+        for script in &[&copy, &paste] {
+            fs::set_permissions(script, fs::Permissions::from_mode(0o700))?;
+        }
+        fs::write(&clipboard, "previous")?;
+
+        // Don't spawn a process to clear the clipboard
+        let config = Config::from_str(&format!(
+            r#"
+            name = "client 1"
+            url = "test.onion"
+
+            [clipboard]
+            copy = {:?}
+            paste = {:?}
+            timeout = 0
+        "#,
+            copy, paste
+        ))?;
+
+        super::copy(&config, "secret")?;
+        assert_eq!(fs::read_to_string(&clipboard)?, "secret");
+
+        let request = ClearRequest {
+            secret: "secret".to_string(),
+            previous: Some("previous".to_string()),
+        };
+
+        // The previous contents are restored
+        super::restore(&config, &request)?;
+        assert_eq!(fs::read_to_string(&clipboard)?, "previous");
+
+        // Something else that's copied in the meantime is kept
+        fs::write(&clipboard, "other")?;
+        super::restore(&config, &request)?;
+        assert_eq!(fs::read_to_string(&clipboard)?, "other");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,91 @@
+use crate::{
+    audit,
+    command::export::secrets,
+    config::Config,
+    net::Client,
+    output::{self, Format},
+    strength::Estimator,
+};
+use anyhow::{bail, Result};
+use log::info;
+
+/// Handle the `audit` command.
+///
+/// Every password is requested and checked for problems, the passwords themselves are never
+/// shown. It fails when there are more findings than configured.
+pub async fn audit(config: Config) -> Result<()> {
+    info!("Auditing all passwords");
+
+    // Setup the HTTP client
+    let client = Client::new(&config)?;
+
+    let passwords = secrets(&client, "").await?;
+
+    let settings = config.audit();
+    let findings = audit::audit(&passwords, &Estimator::new(), settings.min_score());
+
+    match config.format() {
+        Format::Plain => {
+            let width = |column: fn(&audit::Finding) -> usize, header: &str| {
+                findings
+                    .iter()
+                    .map(column)
+                    .chain(Some(header.len()))
+                    .max()
+                    .unwrap_or_default()
+            };
+            let problem_width = width(|finding| finding.problem.to_string().len(), "PROBLEM");
+            let name_width = width(|finding| finding.name.chars().count(), "NAME");
+
+            if !findings.is_empty() {
+                println!(
+                    "{:problem_width$}  {:name_width$}  DETAIL",
+                    "PROBLEM",
+                    "NAME",
+                    problem_width = problem_width,
+                    name_width = name_width
+                );
+                for finding in &findings {
+                    println!(
+                        "{:problem_width$}  {:name_width$}  {}",
+                        finding.problem.to_string(),
+                        finding.name,
+                        finding.detail,
+                        problem_width = problem_width,
+                        name_width = name_width
+                    );
+                }
+                println!();
+            }
+            println!(
+                "{} findings in {} passwords",
+                findings.len(),
+                passwords.len()
+            );
+        }
+        Format::Json => output::print_json(&findings)?,
+        Format::Tsv => {
+            for finding in &findings {
+                println!(
+                    "{}",
+                    output::tsv_row(&[
+                        &finding.problem.to_string(),
+                        &finding.id,
+                        &finding.name,
+                        &finding.detail,
+                    ])
+                );
+            }
+        }
+    }
+
+    if findings.len() > settings.max_findings() {
+        bail!(
+            "The audit has {} findings, more than the {} allowed by max_findings",
+            findings.len(),
+            settings.max_findings()
+        );
+    }
+
+    Ok(())
+}
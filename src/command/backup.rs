@@ -0,0 +1,154 @@
+use crate::{
+    backup::{self, BackupEntry, Header},
+    command::{
+        import::{check, import},
+        ImportOptions,
+    },
+    config::Config,
+    file::create_private_file,
+    import::Record,
+    net::Client,
+    output::{self, Format},
+    prompt, vault,
+};
+use anyhow::{anyhow, bail, Result};
+use log::info;
+use serde::Serialize;
+use std::{convert::TryFrom, fs, io::Write, path::Path};
+
+/// Summary of a backup in machine-readable output.
+///
+/// As TSV the columns are `file`, `version`, `created` and `entries`.
+#[derive(Debug, Serialize)]
+struct BackupInfo<'a> {
+    /// The backup file.
+    file: &'a Path,
+    /// Version of the format.
+    version: u16,
+    /// When the backup was created, in seconds since the UNIX epoch.
+    created: u64,
+    /// Number of passwords in the backup.
+    entries: u32,
+}
+
+/// Handle the `backup` command.
+///
+/// Every password is requested from the server and written to a new file that's encrypted with
+/// a passphrase. The server can't return passwords that share their name with another one, they
+/// are listed and left out of the backup.
+pub async fn backup(config: Config, file: &Path) -> Result<()> {
+    info!("Backing up all passwords to {:?}", file);
+
+    // Ask first so nothing is requested when it fails
+    let passphrase = prompt::secret("Backup passphrase", true)?;
+
+    // Setup the HTTP client
+    let client = Client::new(&config)?;
+
+    // Request the actual password of every entry
+    let passwords = vault::passwords(&client).await?;
+    let mut entries = Vec::with_capacity(passwords.len());
+    let mut skipped = 0;
+    for password in &passwords {
+        if vault::ambiguous(&passwords, password) {
+            eprintln!(
+                "Password \"{}\" with ID {} shares its name with another one and is not backed up",
+                password.name(),
+                password.id()
+            );
+            skipped += 1;
+
+            continue;
+        }
+
+        let secret = vault::secret(&client, &passwords, password).await?;
+
+        entries.push(BackupEntry {
+            name: password.name().to_string(),
+            email: password.email().map(String::from),
+            website: password.website().map(String::from),
+            password: secret.password().to_string(),
+        });
+    }
+
+    let header = Header::new(output::now(), u32::try_from(entries.len())?);
+    let bytes = backup::seal(&header, &passphrase, &entries)?;
+
+    // Don't overwrite an older backup
+    create_private_file(file)
+        .and_then(|mut writer| writer.write_all(&bytes))
+        .map_err(|err| anyhow!("Could not write backup {:?}: {}", file, err))?;
+
+    let info = BackupInfo {
+        file,
+        version: header.version,
+        created: header.created,
+        entries: header.entries,
+    };
+    match config.format() {
+        Format::Plain => println!("Backed up {} passwords to {:?}", info.entries, file),
+        Format::Json => output::print_json(&info)?,
+        Format::Tsv => println!(
+            "{}",
+            output::tsv_row(&[
+                &file.to_string_lossy(),
+                &info.version.to_string(),
+                &info.created.to_string(),
+                &info.entries.to_string(),
+            ])
+        ),
+    }
+
+    if skipped > 0 {
+        bail!(
+            "{} passwords sharing their name with another one are not in the backup, remove the others with kb rm --id ID --force",
+            skipped
+        );
+    }
+
+    Ok(())
+}
+
+/// Handle the `restore-backup` command.
+///
+/// The passwords are imported like any other import, so existing names are handled the same.
+pub async fn restore_backup(config: Config, file: &Path, options: ImportOptions) -> Result<()> {
+    info!("Restoring backup {:?}", file);
+
+    let bytes =
+        fs::read(file).map_err(|err| anyhow!("Could not read backup {:?}: {}", file, err))?;
+
+    // Show what's in it before asking for the passphrase
+    let header = Header::parse(&bytes)?;
+    if config.format() == Format::Plain {
+        println!(
+            "Backup of {} passwords created {}, format version {}",
+            header.entries,
+            output::ago(output::now().saturating_sub(header.created)),
+            header.version
+        );
+    }
+
+    let passphrase = prompt::secret("Backup passphrase", false)?;
+    let (_, entries) = backup::open(&bytes, &passphrase)?;
+
+    let records = entries
+        .into_iter()
+        .map(|entry| {
+            (
+                entry.name.clone(),
+                Ok(Record {
+                    name: entry.name,
+                    password: entry.password,
+                    email: entry.email,
+                    website: entry.website,
+                    skipped: Vec::new(),
+                }),
+            )
+        })
+        .collect();
+
+    let failed = import(&config, records, options).await?;
+
+    check(failed.len())
+}
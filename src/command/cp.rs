@@ -0,0 +1,53 @@
+use crate::{
+    config::Config,
+    net::Client,
+    output::{Entry, Format},
+    prompt,
+    vault::{self, Metadata, Selection},
+};
+use anyhow::{bail, Result};
+use log::info;
+
+/// Handle the invoked command.
+///
+/// The metadata of the source is used for the fields that aren't passed.
+pub async fn cp(
+    config: Config,
+    selection: &Selection<'_>,
+    destination: &str,
+    metadata: &Metadata,
+) -> Result<()> {
+    let source = selection.name;
+    info!("Copying password \"{}\" to \"{}\"", source, destination);
+
+    // Setup the HTTP client
+    let client = Client::new(&config)?;
+
+    // Find the password to copy and make sure the destination is free
+    let passwords = vault::passwords(&client).await?;
+    let password = selection.pick(&passwords, prompt::interactive())?;
+    if passwords
+        .iter()
+        .any(|password| password.name() == destination)
+    {
+        bail!("Password with name \"{}\" already exists", destination);
+    }
+
+    // Register the secret under the new name
+    let secret = vault::secret(&client, &passwords, password).await?;
+    let request = metadata
+        .clone()
+        .or_from(password)
+        .request(destination, secret.password());
+    let response = vault::register(&client, &request).await?;
+
+    info!("Password successfully copied with ID: {}", response.id());
+
+    // Never show the secret
+    match config.format() {
+        Format::Plain => println!("Copied password \"{}\" to \"{}\"", source, destination),
+        format => Entry::new(&response).print(format)?,
+    }
+
+    Ok(())
+}
@@ -1,7 +1,286 @@
-use crate::config::Config;
-use anyhow::Result;
+use crate::{
+    config::Config,
+    net::Client,
+    prompt, validate,
+    vault::{self, Selection},
+};
+use anyhow::{anyhow, bail, ensure, Result};
+use keybear_core::types::RegisterPasswordRequest;
+use log::{debug, info};
+use std::{
+    env,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    process::Command,
+};
+use tempfile::{Builder, NamedTempFile};
+
+/// Memory backed directory that's used for the temporary file when it exists.
+const TMPFS_DIR: &str = "/dev/shm";
+/// Editor that's used when neither `$VISUAL` nor `$EDITOR` are set.
+const DEFAULT_EDITOR: &str = "vi";
+/// Line between the metadata and the password in the edited file.
+const SEPARATOR: &str = "--- password below ---";
 
 /// Handle the invoked command.
-pub fn edit(config: Config, name: &str) -> Result<()> {
+pub async fn edit(config: Config, selection: &Selection<'_>) -> Result<()> {
+    let name = selection.name;
+    info!("Editing password with name \"{}\"", name);
+
+    // Setup the HTTP client
+    let client = Client::new(&config)?;
+
+    // Find the password and get the actual secret
+    let passwords = vault::passwords(&client).await?;
+    let password = selection.pick(&passwords, prompt::interactive())?;
+    let response = vault::secret(&client, &passwords, password).await?;
+
+    let entry = Entry {
+        password: response.password().to_string(),
+        email: password.email().map(|email| email.to_string()),
+        website: password.website().map(|website| website.to_string()),
+    };
+
+    // Let the user edit the entry in a private temporary file
+    let edited = {
+        let file = SecretFile::new(&entry.to_text())?;
+        open_editor(file.path())?;
+
+        Entry::from_text(&file.read()?)?
+    };
+
+    if edited == entry {
+        println!("Password \"{}\" is unchanged", name);
+
+        return Ok(());
+    }
+
+    // Check changed metadata like insert does, existing values are kept as they are
+    if let Some(email) = edited
+        .email
+        .as_deref()
+        .filter(|_| edited.email != entry.email)
+    {
+        validate::email(email)?;
+    }
+    if let Some(website) = edited
+        .website
+        .as_deref()
+        .filter(|_| edited.website != entry.website)
+    {
+        validate::website(website)?;
+    }
+
+    // Build the request object
+    let request = RegisterPasswordRequest::new(
+        name,
+        edited.password,
+        edited.email.as_deref(),
+        edited.website.as_deref(),
+    );
+
+    // Replace the old password with the edited one
+    let response = vault::replace(&client, password, &request).await?;
+
+    info!("Password successfully updated with ID: {}", response.id());
+
+    println!("Successfully updated password \"{}\"", name);
+
     Ok(())
 }
+
+/// A password with the metadata that can be edited.
+#[derive(Debug, Eq, PartialEq)]
+struct Entry {
+    /// The actual password.
+    password: String,
+    /// Associated e-mail.
+    email: Option<String>,
+    /// Associated website.
+    website: Option<String>,
+}
+
+impl Entry {
+    /// Render the entry as text, the metadata on top and the password below the separator.
+    ///
+    /// The password comes last so it can span multiple lines.
+    fn to_text(&self) -> String {
+        format!(
+            "email: {}\nwebsite: {}\n{}\n{}\n",
+            self.email.as_deref().unwrap_or_default(),
+            self.website.as_deref().unwrap_or_default(),
+            SEPARATOR,
+            self.password
+        )
+    }
+
+    /// Parse the text as rendered by `to_text`.
+    fn from_text(text: &str) -> Result<Self> {
+        let (metadata, password) = text
+            .split_once(&format!("{}\n", SEPARATOR))
+            .ok_or_else(|| anyhow!("Missing \"{}\" line above the password", SEPARATOR))?;
+
+        // Editors add a newline at the end of the file, everything else belongs to the password
+        let password = password.strip_suffix('\n').unwrap_or(password).to_string();
+        ensure!(!password.is_empty(), "Password can't be empty");
+
+        let mut entry = Self {
+            password,
+            email: None,
+            website: None,
+        };
+
+        for line in metadata.lines().filter(|line| !line.trim().is_empty()) {
+            let (key, value) = line
+                .split_once(':')
+                .ok_or_else(|| anyhow!("Invalid line \"{}\", expected \"key: value\"", line))?;
+
+            // Empty values remove the field
+            let value = Some(value.trim())
+                .filter(|value| !value.is_empty())
+                .map(|value| value.to_string());
+
+            match key.trim() {
+                "email" => entry.email = value,
+                "website" => entry.website = value,
+                other => bail!("Unknown field \"{}\"", other),
+            }
+        }
+
+        Ok(entry)
+    }
+}
+
+/// A temporary file only readable by the current user which is wiped when dropped.
+struct SecretFile {
+    /// The file, it's removed when dropped.
+    file: NamedTempFile,
+}
+
+impl SecretFile {
+    /// Create the file with the contents, in memory when possible.
+    fn new(contents: &str) -> Result<Self> {
+        let dir = if Path::new(TMPFS_DIR).is_dir() {
+            PathBuf::from(TMPFS_DIR)
+        } else {
+            env::temp_dir()
+        };
+
+        // The file is created with mode 0600
+        let mut file = Builder::new()
+            .prefix("kb-")
+            .suffix(".txt")
+            .tempfile_in(&dir)
+            .map_err(|err| anyhow!("Could not create temporary file in {:?}: {}", dir, err))?;
+
+        debug!("Writing password to temporary file {:?}", file.path());
+
+        file.write_all(contents.as_bytes())?;
+        file.flush()?;
+
+        Ok(Self { file })
+    }
+
+    /// The location of the file.
+    fn path(&self) -> &Path {
+        self.file.path()
+    }
+
+    /// Read the contents of the file.
+    fn read(&self) -> Result<String> {
+        fs::read_to_string(self.path())
+            .map_err(|err| anyhow!("Could not read temporary file {:?}: {}", self.path(), err))
+    }
+
+    /// Overwrite the contents of the file with zeroes.
+    fn wipe(&self) -> Result<()> {
+        let len = fs::metadata(self.path())?.len() as usize;
+
+        let mut file = OpenOptions::new().write(true).open(self.path())?;
+        file.write_all(&vec![0; len])?;
+        file.sync_all()?;
+
+        Ok(())
+    }
+}
+
+impl Drop for SecretFile {
+    fn drop(&mut self) {
+        debug!("Wiping temporary file {:?}", self.path());
+
+        if let Err(err) = self.wipe() {
+            eprintln!("Could not wipe temporary file {:?}: {}", self.path(), err);
+        }
+    }
+}
+
+/// Open the file in the editor from `$VISUAL` or `$EDITOR` and wait until it's closed.
+fn open_editor(path: &Path) -> Result<()> {
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or_else(|_| DEFAULT_EDITOR.to_string());
+
+    // The editor can contain arguments, like "code --wait"
+    let mut args = editor.split_whitespace();
+    let program = args
+        .next()
+        .ok_or_else(|| anyhow!("Editor command is empty"))?;
+
+    debug!("Opening {:?} with editor \"{}\"", path, editor);
+
+    let status = Command::new(program)
+        .args(args)
+        .arg(path)
+        .status()
+        .map_err(|err| anyhow!("Could not start editor \"{}\": {}", editor, err))?;
+
+    ensure!(
+        status.success(),
+        "Editor \"{}\" exited with {}",
+        editor,
+        status
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Entry;
+    use anyhow::Result;
+
+    #[test]
+    fn text_roundtrip() -> Result<()> {
+        let entry = Entry {
+            password: "correct horse".to_string(),
+            email: Some("user@example.com".to_string()),
+            website: None,
+        };
+
+        assert_eq!(Entry::from_text(&entry.to_text())?, entry);
+
+        // Passwords can span multiple lines, metadata-like lines are part of it
+        let multiline = Entry {
+            password: "first line\nemail: not@metadata.com\n\nlast line".to_string(),
+            email: None,
+            website: Some("example.com".to_string()),
+        };
+        assert_eq!(Entry::from_text(&multiline.to_text())?, multiline);
+
+        // Removing a value clears the field
+        let edited = Entry::from_text(
+            "email:\nwebsite: example.com\n--- password below ---\nnew password\n",
+        )?;
+        assert_eq!(edited.password, "new password");
+        assert_eq!(edited.email, None);
+        assert_eq!(edited.website.as_deref(), Some("example.com"));
+
+        // An empty password, unknown fields or a missing separator are invalid
+        assert!(Entry::from_text("email: a@b.c\n--- password below ---\n").is_err());
+        assert!(Entry::from_text("username: me\n--- password below ---\npassword").is_err());
+        assert!(Entry::from_text("password\nemail: a@b.c\n").is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,194 @@
+use crate::{
+    config::Config,
+    export::{
+        kdbx::{self, Cipher},
+        Exported, Plaintext,
+    },
+    file::create_private_file,
+    net::Client,
+    prompt, tree, vault,
+};
+use anyhow::{anyhow, bail, ensure, Result};
+use log::info;
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, IsTerminal, Write},
+    path::Path,
+};
+
+/// Handle the `export` command.
+///
+/// The passwords in the prefix folder are written to the file or stdout. Unless `plaintext` is
+/// set it refuses to write to a terminal or a file others can read.
+pub async fn export(
+    config: Config,
+    format: Plaintext,
+    prefix: Option<&str>,
+    file: Option<&Path>,
+    plaintext: bool,
+) -> Result<()> {
+    // Check where it's written before requesting anything
+    check_destination(file, plaintext)?;
+
+    // Setup the HTTP client
+    let client = Client::new(&config)?;
+
+    let prefix = prefix.map(tree::folder).unwrap_or_default();
+    let passwords = secrets(&client, prefix).await?;
+
+    // Only truncate an existing file when the passwords could be requested
+    format.write(writer(file)?, prefix, &passwords)?;
+
+    info!("Exported {} passwords", passwords.len());
+
+    Ok(())
+}
+
+/// Handle the `export --as kdbx` command.
+///
+/// The passwords in the prefix folder are written to a new KeePass database protected by a
+/// master password.
+pub async fn export_kdbx(
+    config: Config,
+    file: &Path,
+    prefix: Option<&str>,
+    cipher: Cipher,
+) -> Result<()> {
+    // Check where it's written before requesting anything
+    ensure!(!file.exists(), "KeePass database {:?} already exists", file);
+
+    let password = prompt::secret("Master password", true)?;
+
+    // Setup the HTTP client
+    let client = Client::new(&config)?;
+
+    let prefix = prefix.map(tree::folder).unwrap_or_default();
+    let passwords = secrets(&client, prefix).await?;
+    let db = kdbx::database(cipher, prefix, &passwords);
+
+    // The database can only be read by the user
+    let writer = create_private_file(file)
+        .map_err(|err| anyhow!("Could not create KeePass database {:?}: {}", file, err))?;
+    kdbx::write(writer, &db, &password)?;
+
+    info!("Exported {} passwords to {:?}", passwords.len(), file);
+
+    Ok(())
+}
+
+/// Request the passwords in the folder with their actual passwords, sorted by name.
+pub(super) async fn secrets(client: &Client<'_>, folder: &str) -> Result<Vec<Exported>> {
+    let all = vault::passwords(client).await?;
+    let mut passwords = all
+        .iter()
+        .filter(|password| tree::contains(folder, password.name()))
+        .collect::<Vec<_>>();
+    passwords.sort_by(|a, b| a.name().cmp(b.name()));
+
+    if passwords.is_empty() && !folder.is_empty() {
+        bail!("No passwords in folder \"{}\"", folder);
+    }
+
+    let mut exported = Vec::with_capacity(passwords.len());
+    for password in &passwords {
+        let secret = vault::secret(client, &all, password).await?;
+
+        exported.push(Exported {
+            id: password.id().to_string(),
+            name: password.name().to_string(),
+            email: password.email().map(String::from),
+            website: password.website().map(String::from),
+            password: secret.password().to_string(),
+        });
+    }
+
+    Ok(exported)
+}
+
+/// Check that the plaintext passwords can be written to the file, or stdout when not set.
+fn check_destination(file: Option<&Path>, plaintext: bool) -> Result<()> {
+    const OPT_IN: &str = "use --i-know-this-is-plaintext to do it anyway";
+
+    if plaintext {
+        return Ok(());
+    }
+
+    match file {
+        Some(file) => {
+            if fs::metadata(file).is_ok_and(|metadata| world_readable(&metadata)) {
+                bail!(
+                    "Refusing to write plaintext passwords to {:?} which everyone can read, {}",
+                    file,
+                    OPT_IN
+                );
+            }
+        }
+        None => {
+            if io::stdout().is_terminal() {
+                bail!(
+                    "Refusing to print plaintext passwords to the terminal, {}",
+                    OPT_IN
+                );
+            }
+            if stdout_file()?.is_some_and(|metadata| world_readable(&metadata)) {
+                bail!(
+                    "Refusing to write plaintext passwords to a file everyone can read, {}",
+                    OPT_IN
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Open the file, or stdout when not set, for the plaintext passwords.
+fn writer(file: Option<&Path>) -> Result<Box<dyn Write>> {
+    match file {
+        Some(file) => {
+            // New files can only be read by the user
+            let mut options = OpenOptions::new();
+            options.write(true).create(true).truncate(true);
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::OpenOptionsExt;
+                options.mode(0o600);
+            }
+
+            Ok(Box::new(options.open(file).map_err(|err| {
+                anyhow!("Could not create export file {:?}: {}", file, err)
+            })?))
+        }
+        None => Ok(Box::new(io::stdout())),
+    }
+}
+
+/// The metadata of the file stdout is redirected to, `None` when it's something else.
+#[cfg(unix)]
+fn stdout_file() -> Result<Option<fs::Metadata>> {
+    use std::{fs::File, os::fd::AsFd};
+
+    let metadata = File::from(io::stdout().as_fd().try_clone_to_owned()?).metadata()?;
+
+    Ok(Some(metadata).filter(fs::Metadata::is_file))
+}
+
+/// The metadata of the file stdout is redirected to, `None` when it's something else.
+#[cfg(not(unix))]
+fn stdout_file() -> Result<Option<fs::Metadata>> {
+    Ok(None)
+}
+
+/// Whether other users can read the file.
+#[cfg(unix)]
+fn world_readable(metadata: &fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    metadata.permissions().mode() & 0o004 != 0
+}
+
+/// Whether other users can read the file.
+#[cfg(not(unix))]
+fn world_readable(_metadata: &fs::Metadata) -> bool {
+    false
+}
@@ -0,0 +1,187 @@
+use crate::{
+    config::Config,
+    net::Client,
+    output::{self, Entry, Format},
+    vault,
+};
+use anyhow::{bail, Result};
+use fuzzy_matcher::{skim::SkimMatcherV2, FuzzyMatcher};
+use keybear_core::types::PublicPassword;
+use log::info;
+use regex::{Regex, RegexBuilder};
+
+/// How the query is matched against the passwords.
+pub enum Matcher {
+    /// Case-insensitive substring.
+    Substring(String),
+    /// Case-insensitive regular expression.
+    Regex(Regex),
+    /// Fuzzy match ranked by score.
+    Fuzzy(String, Box<SkimMatcherV2>),
+}
+
+impl Matcher {
+    /// Match the query as a case-insensitive substring.
+    pub fn substring(query: &str) -> Self {
+        Matcher::Substring(query.to_lowercase())
+    }
+
+    /// Match the query as a case-insensitive regular expression.
+    pub fn regex(query: &str) -> Result<Self> {
+        Ok(Matcher::Regex(
+            RegexBuilder::new(query).case_insensitive(true).build()?,
+        ))
+    }
+
+    /// Match the query fuzzily.
+    pub fn fuzzy(query: &str) -> Self {
+        Matcher::Fuzzy(
+            query.to_string(),
+            Box::new(SkimMatcherV2::default().ignore_case()),
+        )
+    }
+
+    /// Score a single field, `None` when it doesn't match.
+    fn score_field(&self, field: &str) -> Option<i64> {
+        match self {
+            Matcher::Substring(query) => {
+                let field = field.to_lowercase();
+                let position = field.find(query.as_str())?;
+
+                // Prefer exact matches, then matches at the start
+                Some(if field == *query {
+                    3
+                } else if position == 0 {
+                    2
+                } else {
+                    1
+                })
+            }
+            Matcher::Regex(regex) => {
+                let found = regex.find(field)?;
+
+                Some(if found.as_str().len() == field.len() {
+                    3
+                } else if found.start() == 0 {
+                    2
+                } else {
+                    1
+                })
+            }
+            Matcher::Fuzzy(query, matcher) => matcher.fuzzy_match(field, query),
+        }
+    }
+
+    /// Score a password by its best matching field, `None` when nothing matches.
+    ///
+    /// Matches on the name count double so they rank above the same match on the e-mail or
+    /// website.
+    pub fn score(&self, password: &PublicPassword) -> Option<i64> {
+        let name = self.score_field(password.name()).map(|score| score * 2);
+        let others = password
+            .email()
+            .into_iter()
+            .chain(password.website())
+            .filter_map(|field| self.score_field(field));
+
+        name.into_iter().chain(others).max()
+    }
+
+    /// Keep the matching passwords, ordered from the best to the worst match.
+    pub fn rank(&self, passwords: Vec<PublicPassword>) -> Vec<PublicPassword> {
+        let mut matches = passwords
+            .into_iter()
+            .filter_map(|password| Some((self.score(&password)?, password)))
+            .collect::<Vec<_>>();
+
+        // The sort is stable so equal matches keep the order of the server
+        matches.sort_by(|(a, _), (b, _)| b.cmp(a));
+
+        matches.into_iter().map(|(_, password)| password).collect()
+    }
+}
+
+/// Handle the invoked command.
+pub async fn find(config: Config, query: &str, matcher: &Matcher) -> Result<()> {
+    info!("Searching passwords for \"{}\"", query);
+
+    // Setup the HTTP client
+    let client = Client::new(&config)?;
+
+    // Request all passwords and only keep the matching ones
+    let passwords = matcher.rank(vault::passwords(&client).await?);
+    if passwords.is_empty() {
+        bail!("No passwords match \"{}\"", query);
+    }
+
+    // Print the matches
+    match config.format() {
+        // The name followed by the metadata that's set
+        Format::Plain => passwords.iter().for_each(|pass| {
+            let fields = std::iter::once(pass.name())
+                .chain(pass.email())
+                .chain(pass.website())
+                .collect::<Vec<_>>();
+
+            println!("{}", fields.join("\t"));
+        }),
+        Format::Json => output::print_json(&passwords.iter().map(Entry::new).collect::<Vec<_>>())?,
+        Format::Tsv => passwords
+            .iter()
+            .for_each(|pass| println!("{}", Entry::new(pass).tsv())),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Matcher;
+    use anyhow::Result;
+    use keybear_core::types::PublicPassword;
+
+    fn passwords() -> Vec<PublicPassword> {
+        vec![
+            PublicPassword::new::<_, _, _, String>("1", "work/mail", Some("me@work.com"), None),
+            PublicPassword::new("2", "github", Some("me@home.com"), Some("github.com")),
+            PublicPassword::new::<_, _, String, _>("3", "mail", None, Some("mail.home.com")),
+        ]
+    }
+
+    fn names(passwords: Vec<PublicPassword>) -> Vec<String> {
+        passwords
+            .iter()
+            .map(|password| password.name().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn substring() {
+        let matcher = Matcher::substring("MAIL");
+        assert_eq!(names(matcher.rank(passwords())), ["mail", "work/mail"]);
+
+        let matcher = Matcher::substring("home");
+        assert_eq!(names(matcher.rank(passwords())), ["github", "mail"]);
+
+        assert!(Matcher::substring("nothing").rank(passwords()).is_empty());
+    }
+
+    #[test]
+    fn regex() -> Result<()> {
+        let matcher = Matcher::regex(r"^(git|work/)")?;
+        assert_eq!(names(matcher.rank(passwords())), ["work/mail", "github"]);
+
+        assert!(Matcher::regex("(").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn fuzzy() {
+        let matcher = Matcher::fuzzy("gthb");
+        assert_eq!(names(matcher.rank(passwords())), ["github"]);
+
+        let matcher = Matcher::fuzzy("wml");
+        assert_eq!(names(matcher.rank(passwords())), ["work/mail"]);
+    }
+}
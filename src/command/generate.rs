@@ -1,35 +1,92 @@
-use crate::{config::Config, net::Client};
-use anyhow::Result;
-use chbs::{config::BasicConfig, scheme::ToScheme};
-use keybear_core::{
-    route::v1,
-    types::{PublicPassword, RegisterPasswordRequest},
+use crate::{
+    clipboard,
+    config::Config,
+    generator::Generator,
+    net::Client,
+    output::{Entry, Format},
+    vault::{self, Metadata, Selection},
 };
-use log::info;
+use anyhow::Result;
+use log::{info, warn};
 
 /// Handle the invoked command.
-pub async fn generate(config: Config, name: &str, length: usize, echo: bool) -> Result<()> {
-    info!("Generating and inserting new password");
+///
+/// With `in_place` the password of the existing entry is replaced, keeping the metadata that's
+/// not passed.
+pub async fn generate(
+    config: Config,
+    selection: &Selection<'_>,
+    generator: &Generator,
+    metadata: &Metadata,
+    in_place: bool,
+    echo: bool,
+    clip: bool,
+) -> Result<()> {
+    let name = selection.name;
+    if in_place {
+        info!("Generating and replacing password");
+    } else {
+        info!("Generating and inserting new password");
+    }
 
     // Setup the HTTP client
     let client = Client::new(&config)?;
 
     // Generate the password
-    let mut config = BasicConfig::default();
-    config.words = length;
-    let password = config.to_scheme().generate();
+    let password = generator.generate()?;
+
+    // Logged as a warning because that's the level a single `-v` enables
+    warn!("Entropy: {:.1} bits", generator.entropy()?);
+
+    let response = if in_place {
+        // Find the existing password to keep its metadata
+        let old = vault::find(&client, selection).await?;
+
+        // Build the request object
+        let request = metadata.clone().or_from(&old).request(name, &password);
 
-    // Build the request object
-    let request = RegisterPasswordRequest::new::<_, _, String, String>(name, &password, None, None);
+        // Replace the password
+        let response = vault::replace(&client, &old, &request).await?;
 
-    // Request the password
-    let response: PublicPassword = client.post(v1::PASSWORD, &request).await?;
+        info!("Password successfully replaced with ID: {}", response.id());
+
+        response
+    } else {
+        // Build the request object
+        let request = metadata.request(name, &password);
+
+        // Request the password
+        let response = vault::register(&client, &request).await?;
+
+        info!("Password successfully added with ID: {}", response.id());
+
+        response
+    };
+
+    match config.format() {
+        // Echo the password if requested
+        Format::Plain => {
+            if echo {
+                println!("{}", password);
+            }
+        }
+        format => {
+            let entry = Entry::new(&response);
+            if echo {
+                entry.with_password(&password).print(format)?;
+            } else {
+                entry.print(format)?;
+            }
+        }
+    }
 
-    info!("Password successfully added with ID: {}", response.id());
+    // Copy the password if requested
+    if clip {
+        clipboard::copy(&config, &password)?;
 
-    // Echo the password if requested
-    if echo {
-        println!("{}", password);
+        if config.format() == Format::Plain {
+            println!("Copied password \"{}\" to the clipboard", name);
+        }
     }
 
     Ok(())
@@ -0,0 +1,437 @@
+use crate::{
+    config::Config,
+    import::{
+        csv::{Mapping, Preset, Table},
+        kdbx, pass, Conflict, Record,
+    },
+    net::Client,
+    output::{self, Format},
+    prompt, vault,
+};
+use anyhow::{anyhow, bail, Error, Result};
+use keybear_core::types::PublicPassword;
+use log::{info, warn};
+use serde::Serialize;
+use std::{
+    fmt::{self, Display, Formatter},
+    fs::File,
+    path::{Path, PathBuf},
+};
+
+/// How an import is done.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImportOptions {
+    /// What to do with passwords that have the name of an existing one.
+    pub conflict: Conflict,
+    /// Only report what would be imported.
+    pub dry_run: bool,
+}
+
+/// What happened with a single imported password.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Status {
+    /// Registered as a new password.
+    Added,
+    /// Replaced an existing password with the same name.
+    Overwritten,
+    /// Registered with another name because the name was taken.
+    Renamed,
+    /// Not imported because the name was taken.
+    Skipped,
+    /// Could not be read or registered.
+    Failed,
+}
+
+impl Display for Status {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Status::Added => "added",
+            Status::Overwritten => "overwritten",
+            Status::Renamed => "renamed",
+            Status::Skipped => "skipped",
+            Status::Failed => "failed",
+        })
+    }
+}
+
+/// Report line of a single imported password.
+///
+/// As TSV the columns are `source`, `name`, `status` and `message`.
+#[derive(Debug, Serialize)]
+struct Outcome {
+    /// Where the password came from, like its name or row in the source.
+    source: String,
+    /// Name the password got, `null` when it wasn't imported.
+    name: Option<String>,
+    /// What happened.
+    status: Status,
+    /// Why it was skipped or failed, or which fields were left out when it was imported.
+    message: Option<String>,
+}
+
+impl Outcome {
+    /// The password was imported with the name, without the skipped fields.
+    fn imported(source: String, name: String, status: Status, skipped: &[String]) -> Self {
+        Self {
+            source,
+            name: Some(name),
+            status,
+            message: if skipped.is_empty() {
+                None
+            } else {
+                Some(format!("Skipped fields: {}", skipped.join(", ")))
+            },
+        }
+    }
+
+    /// The password was not imported.
+    fn not_imported(source: String, status: Status, message: String) -> Self {
+        Self {
+            source,
+            name: None,
+            status,
+            message: Some(message),
+        }
+    }
+
+    /// The password could not be imported because of the error.
+    fn failed(source: String, err: Error) -> Self {
+        Self::not_imported(source, Status::Failed, format!("{:#}", err))
+    }
+}
+
+/// Handle the `import pass` command.
+pub async fn import_pass(config: Config, dir: Option<&Path>, options: ImportOptions) -> Result<()> {
+    let dir = match dir {
+        Some(dir) => dir.to_path_buf(),
+        None => pass::default_dir()?,
+    };
+
+    info!("Importing passwords from password store {:?}", dir);
+
+    let records = pass::read(&dir)?;
+
+    let failed = import(&config, records, options).await?;
+
+    check(failed.len())
+}
+
+/// Handle the `import csv` command.
+///
+/// Without a mapping the preset is detected from the columns. Rows that fail are written with
+/// their plaintext passwords to the report file, next to the CSV file when not set.
+pub async fn import_csv(
+    config: Config,
+    file: &Path,
+    mapping: Option<Mapping>,
+    report: Option<&Path>,
+    options: ImportOptions,
+) -> Result<()> {
+    info!("Importing passwords from CSV file {:?}", file);
+
+    // Check the report can be written before anything is imported
+    let report = report.map_or_else(|| report_path(file), Path::to_path_buf);
+    if !options.dry_run && report.exists() {
+        bail!(
+            "Report file {:?} already exists, remove it or choose another one with --report",
+            report
+        );
+    }
+
+    let table = Table::read(
+        File::open(file).map_err(|err| anyhow!("Could not open {:?}: {}", file, err))?,
+    )?;
+
+    let mapping = match mapping {
+        Some(mapping) => mapping,
+        None => {
+            let preset = Preset::detect(&table.headers).ok_or_else(|| {
+                anyhow!("Unknown CSV columns, pass a --preset or a --map of the columns")
+            })?;
+            info!("Detected CSV export of {:?}", preset);
+
+            Mapping::from(preset)
+        }
+    };
+
+    let records = table
+        .records(&mapping)?
+        .into_iter()
+        .zip(&table.rows)
+        .map(|(record, (line, _))| (format!("line {}", line), record))
+        .collect();
+
+    let failed = import(&config, records, options).await?;
+
+    if !failed.is_empty() && !options.dry_run {
+        table.write_report(&report, &failed)?;
+
+        warn!(
+            "Rows that failed are written with their plaintext passwords to {:?}, delete it when done",
+            report
+        );
+    }
+
+    check(failed.len())
+}
+
+/// Handle the `import kdbx` command.
+///
+/// The master password is asked on the terminal unless only the key file is used.
+pub async fn import_kdbx(
+    config: Config,
+    file: &Path,
+    key_file: Option<&Path>,
+    ask_password: bool,
+    options: ImportOptions,
+) -> Result<()> {
+    info!("Importing passwords from KeePass database {:?}", file);
+
+    let password = if ask_password {
+        Some(prompt::hidden(&format!("Master password of {:?}: ", file))?)
+    } else {
+        None
+    };
+
+    let db = kdbx::open(file, password.as_deref(), key_file)?;
+    let records = kdbx::records(&db);
+
+    let failed = import(&config, records, options).await?;
+
+    check(failed.len())
+}
+
+/// Handle the `import firefox` command.
+///
+/// The primary password is asked on the terminal when the profile has one.
+#[cfg(feature = "firefox")]
+pub async fn import_firefox(config: Config, profile: &Path, options: ImportOptions) -> Result<()> {
+    use crate::import::firefox;
+
+    info!("Importing logins from Firefox profile {:?}", profile);
+
+    let key = match firefox::key(profile, "")? {
+        Some(key) => key,
+        None => {
+            let password = prompt::hidden("Primary password of the Firefox profile: ")?;
+
+            firefox::key(profile, &password)?
+                .ok_or_else(|| anyhow!("Primary password is incorrect"))?
+        }
+    };
+    let records = firefox::read(profile, &key)?;
+
+    let failed = import(&config, records, options).await?;
+
+    check(failed.len())
+}
+
+/// Handle the `import firefox` command when it's not built in.
+#[cfg(not(feature = "firefox"))]
+pub async fn import_firefox(
+    _config: Config,
+    _profile: &Path,
+    _options: ImportOptions,
+) -> Result<()> {
+    bail!("Importing from Firefox is not supported, kb was built without the \"firefox\" feature")
+}
+
+/// Fail when some passwords could not be imported.
+pub(super) fn check(failed: usize) -> Result<()> {
+    if failed > 0 {
+        bail!("{} passwords could not be imported", failed);
+    }
+
+    Ok(())
+}
+
+/// The default report file, next to the imported file.
+fn report_path(file: &Path) -> PathBuf {
+    let stem = file
+        .file_stem()
+        .map(|stem| stem.to_string_lossy())
+        .unwrap_or_default();
+
+    file.with_file_name(format!("{}-failed.csv", stem))
+}
+
+/// Register the passwords and report what happened with every one of them.
+///
+/// Returns the index of every password that failed with the reason.
+pub(super) async fn import(
+    config: &Config,
+    records: Vec<(String, Result<Record>)>,
+    options: ImportOptions,
+) -> Result<Vec<(usize, String)>> {
+    // Setup the HTTP client
+    let client = Client::new(config)?;
+
+    // The existing passwords are needed to find conflicting names
+    let mut existing = vault::passwords(&client).await?;
+    let mut imported = Vec::new();
+
+    let mut outcomes = Vec::with_capacity(records.len());
+    for (source, record) in records {
+        let outcome = match record.and_then(|record| record.validate().map(|_| record)) {
+            Ok(record) => {
+                import_record(
+                    &client,
+                    &mut existing,
+                    &mut imported,
+                    source,
+                    record,
+                    options,
+                )
+                .await
+            }
+            Err(err) => Outcome::failed(source, err),
+        };
+
+        outcomes.push(outcome);
+    }
+
+    report(config.format(), &outcomes, options.dry_run)?;
+
+    Ok(outcomes
+        .into_iter()
+        .enumerate()
+        .filter(|(_, outcome)| outcome.status == Status::Failed)
+        .map(|(index, outcome)| (index, outcome.message.unwrap_or_default()))
+        .collect())
+}
+
+/// Register a single password, resolving a conflicting name.
+async fn import_record(
+    client: &Client<'_>,
+    existing: &mut Vec<PublicPassword>,
+    imported: &mut Vec<PublicPassword>,
+    source: String,
+    record: Record,
+    options: ImportOptions,
+) -> Outcome {
+    let conflicting = existing
+        .iter()
+        .chain(imported.iter())
+        .find(|password| password.name() == record.name)
+        .cloned();
+
+    let (name, status, old) = match (conflicting, options.conflict) {
+        (None, _) => (record.name.clone(), Status::Added, None),
+        (Some(_), Conflict::Skip) => {
+            return Outcome::not_imported(
+                source,
+                Status::Skipped,
+                format!("Password with name \"{}\" already exists", record.name),
+            )
+        }
+        (Some(old), Conflict::Overwrite) => (record.name.clone(), Status::Overwritten, Some(old)),
+        (Some(_), Conflict::Rename) => {
+            let taken = existing
+                .iter()
+                .chain(imported.iter())
+                .cloned()
+                .collect::<Vec<_>>();
+
+            (free_name(&taken, &record.name), Status::Renamed, None)
+        }
+    };
+
+    let new = if options.dry_run {
+        // Pretend it's registered so later conflicts are found
+        PublicPassword::new::<_, _, String, String>("", &name, None, None)
+    } else {
+        let request = record.metadata().request(&name, &record.password);
+        let result = match &old {
+            Some(old) => vault::replace(client, old, &request).await,
+            None => vault::register(client, &request).await,
+        };
+
+        match result {
+            Ok(new) => new,
+            Err(err) => return Outcome::failed(source, err),
+        }
+    };
+
+    if let Some(old) = old {
+        existing.retain(|password| password.id() != old.id());
+        imported.retain(|password| password.id() != old.id());
+    }
+    imported.push(new);
+
+    Outcome::imported(source, name, status, &record.skipped)
+}
+
+/// Find a name that's not taken by appending a number.
+fn free_name(existing: &[PublicPassword], name: &str) -> String {
+    (2..)
+        .map(|number| format!("{}-{}", name, number))
+        .find(|candidate| !existing.iter().any(|password| password.name() == candidate))
+        .expect("Ran out of numbers")
+}
+
+/// Print what happened with every password.
+fn report(format: Format, outcomes: &[Outcome], dry_run: bool) -> Result<()> {
+    match format {
+        Format::Plain => {
+            outcomes.iter().for_each(|outcome| {
+                let detail = match &outcome.name {
+                    Some(name) if *name != outcome.source => {
+                        format!("{} -> {}", outcome.source, name)
+                    }
+                    _ => outcome.source.clone(),
+                };
+                let detail = match &outcome.message {
+                    Some(message) => format!("{}: {}", detail, message),
+                    None => detail,
+                };
+
+                println!("{:<12}{}", outcome.status, detail);
+            });
+
+            let imported = outcomes
+                .iter()
+                .filter(|outcome| outcome.name.is_some())
+                .count();
+            if dry_run {
+                println!(
+                    "Dry run, would import {} of {} passwords",
+                    imported,
+                    outcomes.len()
+                );
+            } else {
+                println!("Imported {} of {} passwords", imported, outcomes.len());
+            }
+        }
+        Format::Json => output::print_json(&outcomes)?,
+        Format::Tsv => outcomes.iter().for_each(|outcome| {
+            println!(
+                "{}",
+                output::tsv_row(&[
+                    &outcome.source,
+                    outcome.name.as_deref().unwrap_or_default(),
+                    &outcome.status.to_string(),
+                    outcome.message.as_deref().unwrap_or_default(),
+                ])
+            )
+        }),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use keybear_core::types::PublicPassword;
+
+    #[test]
+    fn free_name() {
+        let existing = vec![
+            PublicPassword::new::<_, _, String, String>("1", "mail", None, None),
+            PublicPassword::new::<_, _, String, String>("2", "mail-2", None, None),
+        ];
+
+        assert_eq!(super::free_name(&existing, "mail"), "mail-3");
+        assert_eq!(super::free_name(&existing, "github"), "github-2");
+    }
+}
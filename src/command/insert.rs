@@ -1,29 +1,58 @@
-use crate::{config::Config, net::Client};
-use anyhow::Result;
-use keybear_core::{
-    route::v1,
-    types::{PublicPassword, RegisterPasswordRequest},
+use crate::{
+    config::Config,
+    net::Client,
+    output::{Entry, Format},
+    prompt,
+    vault::{self, Metadata},
 };
+use anyhow::Result;
 use log::info;
 
 /// Handle the invoked command.
-pub async fn insert(config: Config, name: &str, password: &str, echo: bool) -> Result<()> {
+///
+/// When no password is passed it's read from the terminal or stdin.
+pub async fn insert(
+    config: Config,
+    name: &str,
+    password: Option<&str>,
+    metadata: &Metadata,
+    multiline: bool,
+    echo: bool,
+) -> Result<()> {
+    // Ask for the password when it's not passed as an argument
+    let password = match password {
+        Some(password) => password.to_string(),
+        None => prompt::password(multiline)?,
+    };
+
     info!("Inserting new password");
 
     // Setup the HTTP client
     let client = Client::new(&config)?;
 
     // Build the request object
-    let request = RegisterPasswordRequest::new::<_, _, String, String>(name, password, None, None);
+    let request = metadata.request(name, &password);
 
     // Request the password
-    let response: PublicPassword = client.post(v1::PASSWORD, &request).await?;
+    let response = vault::register(&client, &request).await?;
 
     info!("Password successfully added with ID: {}", response.id());
 
-    // Echo the password if requested
-    if echo {
-        println!("{}", password);
+    match config.format() {
+        // Echo the password if requested
+        Format::Plain => {
+            if echo {
+                println!("{}", password);
+            }
+        }
+        format => {
+            let entry = Entry::new(&response);
+            if echo {
+                entry.with_password(&password).print(format)?;
+            } else {
+                entry.print(format)?;
+            }
+        }
     }
 
     Ok(())
@@ -1,29 +1,57 @@
-use crate::{config::Config, net::Client};
-use anyhow::Result;
-use keybear_core::{route::v1, types::PublicPassword};
+use crate::{
+    config::Config,
+    net::Client,
+    output::{self, Entry, Format},
+    tree::{self, Tree},
+    vault,
+};
+use anyhow::{bail, Result};
 use log::info;
 
 /// Handle the invoked command.
-pub async fn ls(config: Config) -> Result<()> {
+///
+/// Only the passwords in the folder are listed, as a tree unless `flat` is set.
+pub async fn ls(config: Config, folder: Option<&str>, flat: bool) -> Result<()> {
     info!("Retrieving all password names");
 
     // Setup the HTTP client
     let client = Client::new(&config)?;
 
-    // Request the password
-    let response: Vec<PublicPassword> = client.get::<(), _, _>(v1::PASSWORD, None).await?;
+    // Request the passwords in the folder, sorted by name
+    let folder = folder.map(tree::folder).unwrap_or_default();
+    let mut passwords = vault::passwords(&client)
+        .await?
+        .into_iter()
+        .filter(|pass| tree::contains(folder, pass.name()))
+        .collect::<Vec<_>>();
+    passwords.sort_by(|a, b| a.name().cmp(b.name()));
+
+    if passwords.is_empty() && !folder.is_empty() {
+        bail!("No passwords in folder \"{}\"", folder);
+    }
 
     // Print the passwords
-    response.into_iter().for_each(|pass| {
-        println!("name:\t{}", pass.name());
-        println!("id:\t{}", pass.id());
-        if let Some(email) = pass.email() {
-            println!("email:\t{}", email);
-        }
-        if let Some(website) = pass.website() {
-            println!("website:\t{}", website);
+    match config.format() {
+        Format::Plain if flat => passwords
+            .iter()
+            .for_each(|pass| println!("{}", pass.name())),
+        Format::Plain => {
+            let root = if folder.is_empty() {
+                "Passwords"
+            } else {
+                folder
+            };
+
+            print!(
+                "{}",
+                Tree::new(folder, passwords.iter().map(|pass| pass.name())).render(root)
+            );
         }
-    });
+        Format::Json => output::print_json(&passwords.iter().map(Entry::new).collect::<Vec<_>>())?,
+        Format::Tsv => passwords
+            .iter()
+            .for_each(|pass| println!("{}", Entry::new(pass).tsv())),
+    }
 
     Ok(())
 }
@@ -1,15 +1,33 @@
+mod audit;
+mod backup;
+mod cp;
 mod edit;
+mod export;
+mod find;
 mod generate;
+mod import;
 mod insert;
 mod ls;
+mod mv;
+mod otp;
 mod register;
 mod rm;
 mod show;
+mod trash;
 
+pub use audit::audit;
+pub use backup::{backup, restore_backup};
+pub use cp::cp;
 pub use edit::edit;
+pub use export::{export, export_kdbx};
+pub use find::{find, Matcher};
 pub use generate::generate;
+pub use import::{import_csv, import_firefox, import_kdbx, import_pass, ImportOptions};
 pub use insert::insert;
 pub use ls::ls;
+pub use mv::mv;
+pub use otp::{otp, otp_insert};
 pub use register::register;
 pub use rm::rm;
-pub use show::show;
+pub use show::{show, Field};
+pub use trash::{restore, trash_ls, trash_purge};
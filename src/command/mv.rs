@@ -0,0 +1,90 @@
+use crate::{
+    command::rm::put_in_trash,
+    config::Config,
+    net::Client,
+    output::{Entry, Format},
+    prompt,
+    trash::Trash,
+    vault::{self, Metadata, Selection},
+};
+use anyhow::{bail, Result};
+use log::info;
+
+/// Handle the invoked command.
+///
+/// The metadata is kept, an existing password with the new name is only replaced with `force`
+/// and kept in the trash like `kb rm` does.
+pub async fn mv(config: Config, selection: &Selection<'_>, new: &str, force: bool) -> Result<()> {
+    let old = selection.name;
+    info!("Renaming password \"{}\" to \"{}\"", old, new);
+
+    if old == new {
+        bail!("Password \"{}\" already has that name", old);
+    }
+
+    // Setup the HTTP client
+    let client = Client::new(&config)?;
+
+    // Find both the password to rename and a possible one with the new name
+    let passwords = vault::passwords(&client).await?;
+    let password = selection.pick(&passwords, prompt::interactive())?;
+    let existing = passwords
+        .iter()
+        .filter(|password| password.name() == new)
+        .collect::<Vec<_>>();
+    let existing = match existing.as_slice() {
+        [] => None,
+        [existing] if force => Some(*existing),
+        [_] => bail!(
+            "Password with name \"{}\" already exists, use --force to replace it",
+            new
+        ),
+        // Only a single password can be moved to the trash by name
+        existing => bail!(
+            "{} passwords with name \"{}\" already exist, remove them with kb rm first",
+            existing.len(),
+            new
+        ),
+    };
+
+    let secret = vault::secret(&client, &passwords, password).await?;
+
+    // Keep the overwritten password in the trash, while it's still the only one with the name
+    let trash = Trash::open(&config)?;
+    let trashed = match existing {
+        Some(existing) => Some(put_in_trash(&client, &trash, &passwords, existing).await?),
+        None => None,
+    };
+
+    // Register the password under the new name and remove the old one
+    let request = Metadata::default()
+        .or_from(password)
+        .request(new, secret.password());
+    let response = match vault::replace(&client, password, &request).await {
+        Ok(response) => response,
+        Err(err) => {
+            // Nothing was overwritten
+            if let Some(path) = &trashed {
+                trash.delete(path)?;
+            }
+
+            return Err(err);
+        }
+    };
+
+    // Remove the password that was overwritten, without keeping a copy when it's still there
+    if let (Some(existing), Some(path)) = (existing, &trashed) {
+        if let Err(err) = vault::remove(&client, existing).await {
+            trash.delete(path)?;
+
+            return Err(err);
+        }
+    }
+
+    match config.format() {
+        Format::Plain => println!("Renamed password \"{}\" to \"{}\"", old, new),
+        format => Entry::new(&response).print(format)?,
+    }
+
+    Ok(())
+}
@@ -0,0 +1,164 @@
+use crate::{
+    clipboard,
+    config::Config,
+    net::Client,
+    otp::{Kind, Otp},
+    output::{self, Entry, Format},
+    prompt,
+    vault::{self, Metadata, Selection},
+};
+use anyhow::{anyhow, bail, Result};
+use log::info;
+use serde::Serialize;
+
+/// A code in machine-readable output.
+///
+/// As TSV the columns are `code` and `remaining`.
+#[derive(Debug, Serialize)]
+struct Code<'a> {
+    /// The one-time password.
+    code: &'a str,
+    /// Seconds the code is still valid, `null` for counter-based codes.
+    remaining: Option<u64>,
+}
+
+/// Handle the `otp insert` command.
+///
+/// The secret is stored as a password in the form of an `otpauth://` URI. When it's not passed
+/// it's read from the terminal or stdin.
+pub async fn otp_insert(config: Config, name: &str, secret: Option<&str>) -> Result<()> {
+    let secret = match secret {
+        Some(secret) => secret.to_string(),
+        None => prompt::secret("OTP secret", false)?,
+    };
+
+    // Check it before storing it
+    let mut otp = secret.parse::<Otp>()?;
+    if otp.label.is_empty() {
+        otp.label = name.to_string();
+    }
+
+    info!("Inserting new one-time password");
+
+    // Setup the HTTP client
+    let client = Client::new(&config)?;
+
+    let request = Metadata::default().request(name, &otp.to_string());
+    let response = vault::register(&client, &request).await?;
+
+    info!("Secret successfully added with ID: {}", response.id());
+
+    if config.format() != Format::Plain {
+        Entry::new(&response).print(config.format())?;
+    }
+
+    Ok(())
+}
+
+/// Handle the `otp` command.
+///
+/// The counter of a counter-based secret is increased on the server before the code is shown.
+/// This isn't atomic, the server can't update a password so the secret is registered again and
+/// the old one removed. When it runs twice at the same time both can show the same code and
+/// leave two secrets with the name, which is an error after the code is shown.
+pub async fn otp(config: Config, selection: &Selection<'_>, clip: bool) -> Result<()> {
+    let name = selection.name;
+    info!("Generating one-time password for \"{}\"", name);
+
+    // Setup the HTTP client
+    let client = Client::new(&config)?;
+
+    let passwords = vault::passwords(&client).await?;
+    let password = selection.pick(&passwords, prompt::interactive())?;
+    let secret = vault::secret(&client, &passwords, password).await?;
+    let mut otp = secret
+        .password()
+        .parse::<Otp>()
+        .map_err(|err| anyhow!("Password \"{}\" is not an OTP secret: {}", name, err))?;
+
+    let (code, remaining) = otp.code(output::now());
+
+    // Store the next counter, remembering the copies another run left
+    let mut duplicates = None;
+    if let Kind::Hotp { counter } = otp.kind {
+        otp.kind = Kind::Hotp {
+            counter: counter
+                .checked_add(1)
+                .ok_or_else(|| anyhow!("Counter of \"{}\" can't be increased", name))?,
+        };
+
+        let request = Metadata::default()
+            .or_from(password)
+            .request(name, &otp.to_string());
+        let new = vault::replace(&client, password, &request).await?;
+
+        // Another run at the same time leaves its own version of the secret
+        let others = vault::passwords(&client)
+            .await?
+            .into_iter()
+            .filter(|password| password.name() == name && password.id() != new.id())
+            .map(|password| password.id().to_string())
+            .collect::<Vec<_>>();
+        if !others.is_empty() {
+            duplicates = Some((new.id().to_string(), others));
+        }
+    }
+
+    show(&config, name, &code, remaining, clip)?;
+
+    // Only after the code is shown, so it can still be used
+    if let Some((id, others)) = duplicates {
+        bail!(
+            "Secret \"{}\" was changed by another run at the same time and now also exists with the IDs {}, the copy with the counter after this code is the newest one with ID {}, keep it and remove the others with kb rm \"{}\" --id ID --force",
+            name,
+            others.join(", "),
+            id,
+            name
+        );
+    }
+
+    Ok(())
+}
+
+/// Show the code on stdout or copy it to the clipboard.
+fn show(config: &Config, name: &str, code: &str, remaining: Option<u64>, clip: bool) -> Result<()> {
+    let format = config.format();
+    if clip {
+        clipboard::copy(config, code)?;
+
+        if format == Format::Plain {
+            match remaining {
+                Some(remaining) => println!(
+                    "Copied code of \"{}\" to the clipboard, valid for {} more seconds",
+                    name, remaining
+                ),
+                None => println!("Copied code of \"{}\" to the clipboard", name),
+            }
+        }
+
+        return Ok(());
+    }
+
+    match format {
+        Format::Plain => {
+            println!("{}", code);
+
+            // Show it without interfering with scripts reading the code
+            if let Some(remaining) = remaining {
+                eprintln!("Valid for {} more seconds", remaining);
+            }
+        }
+        Format::Json => output::print_json(&Code { code, remaining })?,
+        Format::Tsv => println!(
+            "{}",
+            output::tsv_row(&[
+                code,
+                &remaining
+                    .map(|remaining| remaining.to_string())
+                    .unwrap_or_default(),
+            ])
+        ),
+    }
+
+    Ok(())
+}
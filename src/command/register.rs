@@ -1,6 +1,7 @@
 use crate::{
     config::Config,
     net::{self, ProxyClient},
+    output::{Device, Format},
 };
 use anyhow::{anyhow, ensure, Result};
 use keybear_core::{
@@ -44,6 +45,10 @@ pub async fn register(config: Config) -> Result<()> {
     // Save the ID from the response
     config.save_id(response.id())?;
 
+    if config.format() != Format::Plain {
+        Device::new(response.id(), response.name()).print(config.format())?;
+    }
+
     Ok(())
 }
 
@@ -54,7 +59,7 @@ where
     D: DeserializeOwned,
 {
     // Setup an HTTP client using a Tor proxy
-    let client = Client::new_proxy(&config)?;
+    let client = Client::new_proxy(config)?;
 
     let response = client
         // Create a POST request
@@ -69,7 +74,7 @@ where
     ensure!(
         response.status().is_success(),
         "{}: {}",
-        response.status().to_string(),
+        response.status(),
         response.text().await?
     );
 
@@ -1,39 +1,266 @@
-use crate::{config::Config, net::Client};
-use anyhow::Result;
-use keybear_core::{
-    route::v1,
-    types::{PasswordRequest, PublicPassword},
+use crate::{
+    config::Config,
+    net::Client,
+    output::{self, Entry, Format},
+    prompt,
+    trash::{Trash, Trashed},
+    tree,
+    vault::{self, Selection},
 };
-use log::{debug, info};
-use std::process;
+use anyhow::{anyhow, bail, Result};
+use glob::{MatchOptions, Pattern};
+use keybear_core::types::PublicPassword;
+use log::{error, info};
+use std::path::PathBuf;
 
-/// Handle the invoked command.
-pub async fn rm(config: Config, name: &str) -> Result<()> {
-    info!("Removing password with name \"{}\"", name);
+/// Characters that make a name a glob pattern.
+const GLOB_CHARACTERS: &[char] = &['*', '?', '['];
 
+/// Handle the invoked command.
+///
+/// The name can be a glob pattern, or a folder with `recursive`. Unless `force` is set the
+/// user has to confirm the removal on a terminal. Removed passwords are kept in the trash,
+/// passwords that can't be kept there are only removed with `force`.
+pub async fn rm(
+    config: Config,
+    selection: &Selection<'_>,
+    recursive: bool,
+    force: bool,
+    dry_run: bool,
+) -> Result<()> {
     // Setup the HTTP client
     let client = Client::new(&config)?;
 
-    debug!("Requesting all passwords");
-    // Request the password
-    let passwords: Vec<PublicPassword> = client.get::<(), _, _>(v1::PASSWORD, None).await?;
+    // Find the passwords to remove
+    let all = vault::passwords(&client).await?;
+    let mut passwords = targets(&all, selection, recursive)?;
+
+    // The secret of a password that shares its name with another one can't be requested, so it
+    // can't be kept in the trash and is only removed permanently with force
+    let permanent = passwords
+        .iter()
+        .filter(|password| vault::ambiguous(&all, password))
+        .collect::<Vec<_>>();
+    permanent.iter().for_each(|password| {
+        eprintln!(
+            "Password \"{}\" with ID {} shares its name with another one and can't be kept in the trash",
+            password.name(),
+            password.id()
+        )
+    });
+    let skipped = if force { 0 } else { permanent.len() };
+    if skipped > 0 {
+        passwords.retain(|password| !vault::ambiguous(&all, password));
+        if passwords.is_empty() {
+            bail!("Not removing passwords that can't be kept in the trash, use --force to remove them permanently");
+        }
+    }
 
-    // Find the password matching the name
-    let id = match passwords.iter().find(|password| password.name() == name) {
-        Some(password) => password.id(),
-        None => {
-            eprintln!("Password with name \"{}\" does not exist", name);
-            process::exit(1);
+    // Only show what would be removed
+    if dry_run {
+        match config.format() {
+            Format::Plain => passwords
+                .iter()
+                .for_each(|password| println!("Would remove password \"{}\"", password.name())),
+            format => print(format, &passwords)?,
         }
-    };
 
-    // Build the request object
-    let request = PasswordRequest::from_id(id);
+        return Ok(());
+    }
+
+    // Ask before removing anything
+    if !force {
+        if !prompt::interactive() {
+            bail!("Not removing without confirmation, use --force when not on a terminal");
+        }
+
+        let question = match passwords.as_slice() {
+            [password] => format!("Remove password \"{}\"", password.name()),
+            passwords => {
+                passwords
+                    .iter()
+                    .for_each(|password| eprintln!("{}", password.name()));
+
+                format!("Remove these {} passwords", passwords.len())
+            }
+        };
+        let question = if skipped > 0 {
+            format!(
+                "{}, skipping {} that can't be kept in the trash?",
+                question, skipped
+            )
+        } else {
+            format!("{}?", question)
+        };
+        if !prompt::confirm(&question)? {
+            bail!("Aborted, nothing was removed");
+        }
+    }
+
+    // Remove the passwords, continuing with the others when one fails
+    let trash = Trash::open(&config)?;
+    let mut removed = Vec::new();
+    for password in &passwords {
+        info!("Removing password with name \"{}\"", password.name());
+
+        match remove(&client, &trash, &all, password).await {
+            Ok(()) => {
+                if config.format() == Format::Plain {
+                    println!("Successfully removed password \"{}\"", password.name());
+                }
+
+                removed.push(password.clone());
+            }
+            Err(err) => error!("Could not remove password \"{}\": {}", password.name(), err),
+        }
+    }
+
+    match config.format() {
+        // Summarize bulk removals
+        Format::Plain if passwords.len() > 1 => {
+            println!("Removed {} of {} passwords", removed.len(), passwords.len())
+        }
+        Format::Plain => (),
+        format => print(format, &removed)?,
+    }
+
+    if removed.len() < passwords.len() {
+        bail!(
+            "{} passwords could not be removed",
+            passwords.len() - removed.len()
+        );
+    }
+    if skipped > 0 {
+        bail!(
+            "{} passwords that can't be kept in the trash were not removed, use --force to remove them permanently",
+            skipped
+        );
+    }
+
+    Ok(())
+}
+
+/// Move a password to the trash and remove it from the server.
+///
+/// The server can't return the secret of a password that shares its name with another one, so
+/// such a password is removed without keeping it in the trash.
+async fn remove(
+    client: &Client<'_>,
+    trash: &Trash,
+    passwords: &[PublicPassword],
+    password: &PublicPassword,
+) -> Result<()> {
+    if vault::ambiguous(passwords, password) {
+        return vault::remove(client, password).await;
+    }
+
+    let path = put_in_trash(client, trash, passwords, password).await?;
 
-    // Remove the password
-    let _: () = client.delete(v1::PASSWORD, &request).await?;
+    // Don't keep the copy when the password is still on the server
+    if let Err(err) = vault::remove(client, password).await {
+        trash.delete(&path)?;
 
-    println!("Successfully removed password \"{}\"", name);
+        return Err(err);
+    }
 
     Ok(())
 }
+
+/// Keep a copy of a password in the trash before it's removed from the server.
+pub(super) async fn put_in_trash(
+    client: &Client<'_>,
+    trash: &Trash,
+    passwords: &[PublicPassword],
+    password: &PublicPassword,
+) -> Result<PathBuf> {
+    let secret = vault::secret(client, passwords, password).await?;
+
+    trash.put(&Trashed {
+        id: password.id().to_string(),
+        name: password.name().to_string(),
+        email: password.email().map(String::from),
+        website: password.website().map(String::from),
+        password: secret.password().to_string(),
+        deleted: output::now(),
+    })
+}
+
+/// Find the passwords to remove by name, glob pattern or folder.
+fn targets(
+    passwords: &[PublicPassword],
+    selection: &Selection<'_>,
+    recursive: bool,
+) -> Result<Vec<PublicPassword>> {
+    if recursive {
+        let folder = tree::folder(selection.name);
+        if folder.is_empty() {
+            bail!("Refusing to remove all passwords");
+        }
+
+        let passwords = passwords
+            .iter()
+            .filter(|password| tree::contains(folder, password.name()))
+            .cloned()
+            .collect::<Vec<_>>();
+        if passwords.is_empty() {
+            bail!("No passwords in folder \"{}\"", folder);
+        }
+
+        Ok(passwords)
+    } else if selection.id.is_none() && selection.name.contains(GLOB_CHARACTERS) {
+        let pattern = Pattern::new(selection.name)
+            .map_err(|err| anyhow!("Invalid pattern \"{}\": {}", selection.name, err))?;
+
+        let passwords = passwords
+            .iter()
+            .filter(|password| matches(&pattern, password.name()))
+            .cloned()
+            .collect::<Vec<_>>();
+        if passwords.is_empty() {
+            bail!("No passwords match \"{}\"", selection.name);
+        }
+
+        Ok(passwords)
+    } else {
+        Ok(vec![selection
+            .pick(passwords, prompt::interactive())?
+            .clone()])
+    }
+}
+
+/// Whether the name matches the glob pattern, `*` doesn't match across folders but `**` does.
+fn matches(pattern: &Pattern, name: &str) -> bool {
+    pattern.matches_with(
+        name,
+        MatchOptions {
+            require_literal_separator: true,
+            ..MatchOptions::default()
+        },
+    )
+}
+
+/// Print the passwords in a machine-readable format.
+fn print(format: Format, passwords: &[PublicPassword]) -> Result<()> {
+    match format {
+        Format::Json => output::print_json(&passwords.iter().map(Entry::new).collect::<Vec<_>>()),
+        _ => passwords
+            .iter()
+            .try_for_each(|password| Entry::new(password).print(format)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glob::Pattern;
+
+    #[test]
+    fn matches() {
+        let pattern = Pattern::new("tmp/*").unwrap();
+        assert!(super::matches(&pattern, "tmp/test"));
+        assert!(!super::matches(&pattern, "tmp/nested/test"));
+        assert!(!super::matches(&pattern, "other/test"));
+
+        let pattern = Pattern::new("tmp/**/*").unwrap();
+        assert!(super::matches(&pattern, "tmp/nested/test"));
+    }
+}
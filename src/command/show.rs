@@ -1,27 +1,143 @@
-use crate::{config::Config, net::Client};
-use anyhow::Result;
-use keybear_core::{
-    route::v1,
-    types::{PasswordRequest, PasswordResponse},
+use crate::{
+    clipboard,
+    config::Config,
+    net::Client,
+    output::{self, Entry, Format},
+    prompt,
+    vault::{self, Selection},
 };
+use anyhow::{anyhow, bail, Error, Result};
 use log::info;
+use std::{
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+/// A single field of a password entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    /// The actual password.
+    Password,
+    /// Associated e-mail.
+    Email,
+    /// Associated website.
+    Website,
+    /// Unique identifier.
+    Id,
+}
+
+impl Field {
+    /// Name of the field in machine-readable output.
+    fn key(self) -> &'static str {
+        match self {
+            Field::Password => "password",
+            Field::Email => "email",
+            Field::Website => "website",
+            Field::Id => "id",
+        }
+    }
+}
+
+impl FromStr for Field {
+    type Err = Error;
+
+    fn from_str(field: &str) -> Result<Self> {
+        match field {
+            "password" => Ok(Field::Password),
+            "email" => Ok(Field::Email),
+            "website" => Ok(Field::Website),
+            "id" => Ok(Field::Id),
+            other => bail!("Unknown field \"{}\"", other),
+        }
+    }
+}
+
+impl Display for Field {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Field::Password => "password",
+            Field::Email => "e-mail",
+            Field::Website => "website",
+            Field::Id => "ID",
+        })
+    }
+}
 
 /// Handle the invoked command.
-pub async fn show(config: Config, name: &str) -> Result<()> {
+///
+/// Without a field or `all` the password is shown, with the metadata on stderr. In a
+/// machine-readable format the whole entry is shown instead.
+pub async fn show(
+    config: Config,
+    selection: &Selection<'_>,
+    field: Option<Field>,
+    all: bool,
+    clip: bool,
+) -> Result<()> {
+    let name = selection.name;
     info!("Retrieving password for name \"{}\"", name);
 
     // Setup the HTTP client
     let client = Client::new(&config)?;
 
-    // Build the request object
-    let request = PasswordRequest::from_name(name);
+    // Request the metadata
+    let passwords = vault::passwords(&client).await?;
+    let password = selection.pick(&passwords, prompt::interactive())?;
+
+    let format = config.format();
+    if all || (field.is_none() && !clip && format != Format::Plain) {
+        let response = vault::secret(&client, &passwords, password).await?;
+
+        Entry::new(password)
+            .with_password(response.password())
+            .print(format)?;
+
+        return Ok(());
+    }
+
+    let field = field.unwrap_or(Field::Password);
+    let value = match field {
+        // Only request the password when it's needed
+        Field::Password => vault::secret(&client, &passwords, password)
+            .await?
+            .password()
+            .to_string(),
+        Field::Email => password
+            .email()
+            .ok_or_else(|| anyhow!("Password \"{}\" has no e-mail", name))?
+            .to_string(),
+        Field::Website => password
+            .website()
+            .ok_or_else(|| anyhow!("Password \"{}\" has no website", name))?
+            .to_string(),
+        Field::Id => password.id().to_string(),
+    };
+
+    if clip {
+        clipboard::copy(&config, &value)?;
+
+        if format == Format::Plain {
+            println!("Copied {} of \"{}\" to the clipboard", field, name);
+        }
+
+        return Ok(());
+    }
 
-    // Request the password
-    let response: PasswordResponse = client
-        .post(format!("{}/{}", v1::PASSWORD, name), &request)
-        .await?;
+    match format {
+        Format::Plain => println!("{}", value),
+        Format::Json => output::print_json(&serde_json::json!({ field.key(): value }))?,
+        Format::Tsv => println!("{}", output::tsv_row(&[&value])),
+    }
 
-    println!("{}", response.password());
+    // Show the metadata without interfering with scripts reading the password
+    if field == Field::Password && format == Format::Plain {
+        if let Some(email) = password.email() {
+            eprintln!("email: {}", email);
+        }
+        if let Some(website) = password.website() {
+            eprintln!("website: {}", website);
+        }
+    }
 
     Ok(())
 }
@@ -0,0 +1,162 @@
+use crate::{
+    config::Config,
+    net::Client,
+    output::{self, Entry, Format},
+    prompt,
+    trash::{Trash, Trashed},
+    vault::{self, Metadata},
+};
+use anyhow::{bail, Result};
+use log::info;
+use serde::Serialize;
+
+/// A removed password in machine-readable output.
+///
+/// As TSV the columns are `id`, `name`, `email`, `website` and `deleted`.
+#[derive(Debug, Serialize)]
+struct TrashEntry<'a> {
+    /// ID the password had on the server.
+    id: &'a str,
+    /// Name of the password.
+    name: &'a str,
+    /// Associated e-mail, `null` when not set.
+    email: Option<&'a str>,
+    /// Associated website, `null` when not set.
+    website: Option<&'a str>,
+    /// When the password was removed, in seconds since the UNIX epoch.
+    deleted: u64,
+}
+
+impl<'a> TrashEntry<'a> {
+    /// Output of a removed password, without the actual password.
+    fn new(trashed: &'a Trashed) -> Self {
+        Self {
+            id: &trashed.id,
+            name: &trashed.name,
+            email: trashed.email.as_deref(),
+            website: trashed.website.as_deref(),
+            deleted: trashed.deleted,
+        }
+    }
+}
+
+/// Handle the `trash ls` command.
+pub fn trash_ls(config: Config) -> Result<()> {
+    let trash = Trash::open(&config)?;
+    let entries = trash.list()?;
+
+    match config.format() {
+        Format::Plain => entries.iter().for_each(|(_, trashed)| {
+            println!(
+                "{}\t{}\tremoved {}",
+                trashed.name,
+                trashed.id,
+                output::ago(output::now().saturating_sub(trashed.deleted))
+            )
+        }),
+        Format::Json => output::print_json(
+            &entries
+                .iter()
+                .map(|(_, trashed)| TrashEntry::new(trashed))
+                .collect::<Vec<_>>(),
+        )?,
+        Format::Tsv => entries.iter().for_each(|(_, trashed)| {
+            println!(
+                "{}",
+                output::tsv_row(&[
+                    &trashed.id,
+                    &trashed.name,
+                    trashed.email.as_deref().unwrap_or_default(),
+                    trashed.website.as_deref().unwrap_or_default(),
+                    &trashed.deleted.to_string(),
+                ])
+            )
+        }),
+    }
+
+    Ok(())
+}
+
+/// Handle the `trash purge` command.
+pub fn trash_purge(config: Config, force: bool) -> Result<()> {
+    let trash = Trash::open(&config)?;
+    let entries = trash.list()?;
+    if entries.is_empty() {
+        info!("Trash is already empty");
+
+        return Ok(());
+    }
+
+    // Ask before deleting anything for good
+    if !force {
+        if !prompt::interactive() {
+            bail!("Not purging without confirmation, use --force when not on a terminal");
+        }
+        if !prompt::confirm(&format!(
+            "Permanently delete {} passwords in the trash?",
+            entries.len()
+        ))? {
+            bail!("Aborted, nothing was deleted");
+        }
+    }
+
+    entries
+        .iter()
+        .try_for_each(|(path, _)| trash.delete(path))?;
+
+    if config.format() == Format::Plain {
+        println!("Deleted {} passwords from the trash", entries.len());
+    }
+
+    Ok(())
+}
+
+/// Handle the `restore` command.
+///
+/// The most recently removed password with the name is restored, or the one with the ID it had.
+pub async fn restore(config: Config, name: &str, id: Option<&str>) -> Result<()> {
+    info!("Restoring password with name \"{}\"", name);
+
+    let trash = Trash::open(&config)?;
+    let (path, trashed) = match trash
+        .list()?
+        .into_iter()
+        .find(|(_, trashed)| trashed.name == name && id.is_none_or(|id| trashed.id == id))
+    {
+        Some(entry) => entry,
+        None => bail!("Password with name \"{}\" is not in the trash", name),
+    };
+
+    // Setup the HTTP client
+    let client = Client::new(&config)?;
+
+    // Don't create a second password with the same name
+    if vault::passwords(&client)
+        .await?
+        .iter()
+        .any(|password| password.name() == name)
+    {
+        bail!(
+            "Password with name \"{}\" already exists, rename or remove it first",
+            name
+        );
+    }
+
+    // Register the password again
+    let metadata = Metadata {
+        email: trashed.email.clone(),
+        website: trashed.website.clone(),
+    };
+    let response = vault::register(&client, &metadata.request(name, &trashed.password)).await?;
+
+    info!("Password successfully restored with ID: {}", response.id());
+
+    trash.delete(&path)?;
+
+    match config.format() {
+        Format::Plain => println!("Restored password \"{}\"", name),
+        format => Entry::new(&response).print(format)?,
+    }
+
+    Ok(())
+}
@@ -1,4 +1,8 @@
-use crate::PROJECT_NAME;
+use crate::{
+    generator::{Append, Capitalization},
+    output::Format,
+    PROJECT_NAME,
+};
 use anyhow::{anyhow, Error, Result};
 use directories_next::ProjectDirs;
 use keybear_core::crypto::StaticSecretExt;
@@ -18,6 +22,8 @@ const DEFAULT_SECRET_KEY_FILENAME: &str = "keybear.sk";
 const DEFAULT_SERVER_PUBLIC_KEY_FILENAME: &str = "server.pk";
 /// Client ID file filename.
 const DEFAULT_ID_FILENAME: &str = "keybear.id";
+/// Trash directory name.
+const DEFAULT_TRASH_DIRNAME: &str = "trash";
 
 /// The application configuration file.
 #[derive(Debug, Default, Eq, PartialEq, Deserialize)]
@@ -43,6 +49,89 @@ pub struct Config {
     /// Tor SOCKS5 proxy port.
     #[serde(default = "default_proxy_port")]
     proxy_port: u16,
+
+    /// Output format, can be overwritten with a command line argument.
+    #[serde(default)]
+    format: Format,
+
+    /// Clipboard settings.
+    #[serde(default)]
+    clipboard: ClipboardConfig,
+
+    /// Password generation settings.
+    #[serde(default)]
+    generate: GenerateConfig,
+
+    /// Settings for the trash of removed passwords.
+    #[serde(default)]
+    trash: TrashConfig,
+
+    /// Settings for auditing the passwords.
+    #[serde(default)]
+    audit: AuditConfig,
+
+    /// Where the configuration file was loaded from.
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+/// The clipboard section of the configuration file.
+#[derive(Debug, Eq, PartialEq, Deserialize)]
+pub struct ClipboardConfig {
+    /// Command that puts its stdin on the clipboard, detected when not set.
+    copy: Option<String>,
+
+    /// Command that prints the contents of the clipboard, detected when not set.
+    paste: Option<String>,
+
+    /// Seconds after which the clipboard is cleared, 0 to never clear it.
+    #[serde(default = "default_clipboard_timeout")]
+    timeout: u64,
+}
+
+/// The generate section of the configuration file.
+///
+/// Command line arguments take precedence over these settings.
+#[derive(Debug, Default, Eq, PartialEq, Deserialize)]
+pub struct GenerateConfig {
+    /// Amount of words in a pass-phrase.
+    words: Option<usize>,
+
+    /// What's put between the words of a pass-phrase.
+    separator: Option<String>,
+
+    /// How the words of a pass-phrase are capitalized.
+    capitalize: Option<Capitalization>,
+
+    /// What's appended to a pass-phrase.
+    append: Option<Vec<Append>>,
+
+    /// Wordlist file to generate pass-phrases with.
+    wordlist: Option<PathBuf>,
+}
+
+/// The trash section of the configuration file.
+#[derive(Debug, Eq, PartialEq, Deserialize)]
+pub struct TrashConfig {
+    /// Directory where the removed passwords are kept, next to the secret key when not set.
+    #[serde(default)]
+    path: Option<PathBuf>,
+
+    /// Days after which removed passwords are deleted for good, 0 to keep them forever.
+    #[serde(default = "default_trash_retention")]
+    retention_days: u64,
+}
+
+/// The audit section of the configuration file.
+#[derive(Debug, Eq, PartialEq, Deserialize)]
+pub struct AuditConfig {
+    /// Passwords with a lower strength score, from 0 to 4, are reported as weak.
+    #[serde(default = "default_audit_min_score")]
+    min_score: u8,
+
+    /// The audit fails when it has more findings than this.
+    #[serde(default)]
+    max_findings: usize,
 }
 
 impl Config {
@@ -60,7 +149,15 @@ impl Config {
         let contents = fs::read_to_string(file)
             .map_err(|err| anyhow!("reading configuration file {:?} failed: {}", file, err))?;
 
-        Self::from_str(&contents)
+        let mut config = Self::from_str(&contents)?;
+        config.path = file.to_path_buf();
+
+        Ok(config)
+    }
+
+    /// The location of the configuration file.
+    pub fn path(&self) -> &Path {
+        &self.path
     }
 
     /// The name of this client.
@@ -78,6 +175,44 @@ impl Config {
         self.proxy_port
     }
 
+    /// The output format.
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    /// Overwrite the output format from the configuration file.
+    pub fn set_format(&mut self, format: Format) {
+        self.format = format;
+    }
+
+    /// The clipboard settings.
+    pub fn clipboard(&self) -> &ClipboardConfig {
+        &self.clipboard
+    }
+
+    /// The password generation settings.
+    pub fn generate(&self) -> &GenerateConfig {
+        &self.generate
+    }
+
+    /// The trash settings.
+    pub fn trash(&self) -> &TrashConfig {
+        &self.trash
+    }
+
+    /// Directory where the removed passwords are kept, next to the secret key when not set.
+    pub fn trash_path(&self) -> PathBuf {
+        self.trash
+            .path
+            .clone()
+            .unwrap_or_else(|| self.secret_key_path.with_file_name(DEFAULT_TRASH_DIRNAME))
+    }
+
+    /// The audit settings.
+    pub fn audit(&self) -> &AuditConfig {
+        &self.audit
+    }
+
     /// Create a new secret key and save it to the file.
     pub fn generate_secret_key(&self) -> Result<StaticSecret> {
         debug!(
@@ -124,7 +259,7 @@ impl Config {
         })?)?;
 
         // Try to write the keys as raw bytes to the disk
-        fs::write(&self.id_path, &client_id)
+        fs::write(&self.id_path, client_id)
             .map_err(|err| anyhow!("Could not write ID to file {:?}: {}", self.id_path, err))?;
 
         Ok(())
@@ -204,6 +339,97 @@ impl Config {
     }
 }
 
+impl ClipboardConfig {
+    /// The command to put something on the clipboard.
+    pub fn copy(&self) -> Option<&str> {
+        self.copy.as_deref()
+    }
+
+    /// The command to get the contents of the clipboard.
+    pub fn paste(&self) -> Option<&str> {
+        self.paste.as_deref()
+    }
+
+    /// Seconds after which the clipboard is cleared.
+    pub fn timeout(&self) -> u64 {
+        self.timeout
+    }
+}
+
+impl GenerateConfig {
+    /// Amount of words in a pass-phrase.
+    pub fn words(&self) -> Option<usize> {
+        self.words
+    }
+
+    /// What's put between the words of a pass-phrase.
+    pub fn separator(&self) -> Option<&str> {
+        self.separator.as_deref()
+    }
+
+    /// How the words of a pass-phrase are capitalized.
+    pub fn capitalize(&self) -> Option<Capitalization> {
+        self.capitalize
+    }
+
+    /// What's appended to a pass-phrase.
+    pub fn append(&self) -> Option<&[Append]> {
+        self.append.as_deref()
+    }
+
+    /// Wordlist file to generate pass-phrases with.
+    pub fn wordlist(&self) -> Option<&Path> {
+        self.wordlist.as_deref()
+    }
+}
+
+impl TrashConfig {
+    /// Days after which removed passwords are deleted for good, 0 to keep them forever.
+    pub fn retention_days(&self) -> u64 {
+        self.retention_days
+    }
+}
+
+impl AuditConfig {
+    /// Passwords with a lower strength score are reported as weak.
+    pub fn min_score(&self) -> u8 {
+        self.min_score
+    }
+
+    /// The audit fails when it has more findings than this.
+    pub fn max_findings(&self) -> usize {
+        self.max_findings
+    }
+}
+
+impl Default for ClipboardConfig {
+    fn default() -> Self {
+        Self {
+            copy: None,
+            paste: None,
+            timeout: default_clipboard_timeout(),
+        }
+    }
+}
+
+impl Default for TrashConfig {
+    fn default() -> Self {
+        Self {
+            path: None,
+            retention_days: default_trash_retention(),
+        }
+    }
+}
+
+impl Default for AuditConfig {
+    fn default() -> Self {
+        Self {
+            min_score: default_audit_min_score(),
+            max_findings: 0,
+        }
+    }
+}
+
 impl FromStr for Config {
     type Err = Error;
 
@@ -217,6 +443,21 @@ fn default_proxy_port() -> u16 {
     9050
 }
 
+/// The default amount of seconds after which the clipboard is cleared.
+fn default_clipboard_timeout() -> u64 {
+    45
+}
+
+/// The default amount of days removed passwords are kept.
+fn default_trash_retention() -> u64 {
+    30
+}
+
+/// The default strength score below which passwords are weak.
+fn default_audit_min_score() -> u8 {
+    3
+}
+
 /// The default path where the secret key lives.
 fn default_secret_key_path() -> PathBuf {
     ProjectDirs::from(PROJECT_NAME.0, PROJECT_NAME.1, PROJECT_NAME.2)
@@ -243,9 +484,13 @@ fn default_id_path() -> PathBuf {
 
 #[cfg(test)]
 mod tests {
-    use crate::config::Config;
+    use crate::{
+        config::Config,
+        generator::{Append, Capitalization},
+        output::Format,
+    };
     use anyhow::Result;
-    use std::str::FromStr;
+    use std::{path::Path, str::FromStr};
 
     #[test]
     fn from_toml() -> Result<()> {
@@ -259,6 +504,93 @@ mod tests {
         assert_eq!(config.name(), "client 1");
         assert_eq!(config.url(), "test.onion");
         assert_eq!(config.proxy_port(), 1234);
+        assert_eq!(config.format(), Format::Plain);
+        assert_eq!(config.clipboard().copy(), None);
+        assert_eq!(config.clipboard().timeout(), 45);
+        assert_eq!(config.trash().retention_days(), 30);
+        assert_eq!(config.audit().min_score(), 3);
+        assert_eq!(config.audit().max_findings(), 0);
+
+        let config = Config::from_str(
+            r#"
+            name = "client 1"
+            url = "test.onion"
+            format = "json"
+
+            [clipboard]
+            copy = "xsel --clipboard --input"
+            paste = "xsel --clipboard --output"
+            timeout = 10
+        "#,
+        )?;
+        assert_eq!(config.format(), Format::Json);
+        assert_eq!(config.clipboard().copy(), Some("xsel --clipboard --input"));
+        assert_eq!(
+            config.clipboard().paste(),
+            Some("xsel --clipboard --output")
+        );
+        assert_eq!(config.clipboard().timeout(), 10);
+
+        let config = Config::from_str(
+            r#"
+            name = "client 1"
+            url = "test.onion"
+
+            [generate]
+            words = 6
+            separator = "-"
+            capitalize = "first"
+            append = ["number", "symbol"]
+            wordlist = "/usr/share/dict/eff_large_wordlist.txt"
+        "#,
+        )?;
+        assert_eq!(config.generate().words(), Some(6));
+        assert_eq!(config.generate().separator(), Some("-"));
+        assert_eq!(config.generate().capitalize(), Some(Capitalization::First));
+        assert_eq!(
+            config.generate().append(),
+            Some(&[Append::Number, Append::Symbol][..])
+        );
+        assert_eq!(
+            config.generate().wordlist(),
+            Some(Path::new("/usr/share/dict/eff_large_wordlist.txt"))
+        );
+
+        let config = Config::from_str(
+            r#"
+            name = "client 1"
+            url = "test.onion"
+
+            [trash]
+            path = "/tmp/keybear-trash"
+            retention_days = 7
+        "#,
+        )?;
+        assert_eq!(config.trash_path(), Path::new("/tmp/keybear-trash"));
+        assert_eq!(config.trash().retention_days(), 7);
+
+        // The trash follows the secret key
+        let config = Config::from_str(
+            r#"
+            name = "client 1"
+            url = "test.onion"
+            secret_key_path = "/tmp/keybear/keybear.sk"
+        "#,
+        )?;
+        assert_eq!(config.trash_path(), Path::new("/tmp/keybear/trash"));
+
+        let config = Config::from_str(
+            r#"
+            name = "client 1"
+            url = "test.onion"
+
+            [audit]
+            min_score = 2
+            max_findings = 5
+        "#,
+        )?;
+        assert_eq!(config.audit().min_score(), 2);
+        assert_eq!(config.audit().max_findings(), 5);
 
         // Verify that we get errors when an invalid config is used
         assert!(Config::from_str("*invalid*").is_err());
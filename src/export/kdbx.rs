@@ -0,0 +1,243 @@
+use crate::{
+    export::{relative, Exported},
+    tree::SEPARATOR,
+};
+use anyhow::{anyhow, bail, Error, Result};
+use keepass::{
+    config::{DatabaseConfig, KdfConfig, OuterCipherConfig},
+    db::{Entry, Group, Node, Value},
+    Database, DatabaseKey,
+};
+use secstr::SecStr;
+use std::{io::Write, str::FromStr};
+
+/// Name of the root group of the database.
+const ROOT: &str = "keybear";
+
+/// Argon2id memory in bytes, the same as KeePassXC uses.
+const KDF_MEMORY: u64 = 64 * 1024 * 1024;
+/// Argon2id iterations.
+const KDF_ITERATIONS: u64 = 10;
+/// Argon2id parallelism.
+const KDF_PARALLELISM: u32 = 2;
+
+/// Cipher the database is encrypted with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cipher {
+    /// AES-256, the default of KeePass.
+    Aes,
+    /// ChaCha20.
+    ChaCha20,
+}
+
+impl FromStr for Cipher {
+    type Err = Error;
+
+    fn from_str(cipher: &str) -> Result<Self> {
+        match cipher {
+            "aes" => Ok(Cipher::Aes),
+            "chacha20" => Ok(Cipher::ChaCha20),
+            other => bail!("Unknown cipher \"{}\"", other),
+        }
+    }
+}
+
+/// Create a KDBX 4 database with the passwords, using Argon2id to derive the key.
+///
+/// Folders of the names relative to the prefix become groups, the last part the title of the
+/// entry.
+pub fn database(cipher: Cipher, prefix: &str, passwords: &[Exported]) -> Database {
+    let mut db = Database::new(DatabaseConfig {
+        outer_cipher_config: match cipher {
+            Cipher::Aes => OuterCipherConfig::AES256,
+            Cipher::ChaCha20 => OuterCipherConfig::ChaCha20,
+        },
+        kdf_config: KdfConfig::Argon2id {
+            iterations: KDF_ITERATIONS,
+            memory: KDF_MEMORY,
+            parallelism: KDF_PARALLELISM,
+            version: argon2::Version::Version13,
+        },
+        ..DatabaseConfig::default()
+    });
+    db.root.name = ROOT.to_string();
+    db.meta.database_name = Some(ROOT.to_string());
+
+    for password in passwords {
+        let mut segments = relative(prefix, &password.name)
+            .split(SEPARATOR)
+            .filter(|segment| !segment.is_empty())
+            .collect::<Vec<_>>();
+        let title = segments.pop().unwrap_or(&password.name);
+
+        let parent = segments
+            .into_iter()
+            .fold(&mut db.root, |parent, name| group(parent, name));
+        parent.add_child(entry(title, password));
+    }
+
+    db
+}
+
+/// Encrypt the database with the master password and write it.
+pub fn write<W>(mut writer: W, db: &Database, password: &str) -> Result<()>
+where
+    W: Write,
+{
+    db.save(&mut writer, DatabaseKey::new().with_password(password))
+        .map_err(|err| anyhow!("Could not write KeePass database: {}", err))?;
+    writer.flush()?;
+
+    Ok(())
+}
+
+/// The child group with the name, it's created when there's none yet.
+fn group<'a>(parent: &'a mut Group, name: &str) -> &'a mut Group {
+    let index = parent
+        .children
+        .iter()
+        .position(|node| matches!(node, Node::Group(group) if group.name == name))
+        .unwrap_or_else(|| {
+            parent.add_child(Group::new(name));
+
+            parent.children.len() - 1
+        });
+
+    match &mut parent.children[index] {
+        Node::Group(group) => group,
+        Node::Entry(_) => unreachable!("Only groups are selected"),
+    }
+}
+
+/// Turn a password into an entry, the e-mail is used as the user name.
+fn entry(title: &str, password: &Exported) -> Entry {
+    let mut entry = Entry::new();
+
+    let mut set = |key: &str, value: Value| {
+        entry.fields.insert(key.to_string(), value);
+    };
+    set("Title", Value::Unprotected(title.to_string()));
+    set(
+        "UserName",
+        Value::Unprotected(password.email.clone().unwrap_or_default()),
+    );
+    set(
+        "Password",
+        Value::Protected(SecStr::new(password.password.as_bytes().to_vec())),
+    );
+    set(
+        "URL",
+        Value::Unprotected(password.website.clone().unwrap_or_default()),
+    );
+
+    entry
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Cipher;
+    use crate::{
+        export::{exported, Exported},
+        import,
+    };
+    use anyhow::Result;
+    use keepass::config::KdfConfig;
+    use std::{fs::File, path::Path};
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn roundtrip() -> Result<()> {
+        let passwords = [
+            Exported {
+                email: Some("ci@example.com".to_string()),
+                ..exported(
+                    "1",
+                    "clients/acme/ci/token",
+                    Some("https://ci.example.com"),
+                    "hunter2",
+                )
+            },
+            exported("2", "clients/acme/mail", None, "correct horse"),
+        ];
+
+        for cipher in [Cipher::Aes, Cipher::ChaCha20] {
+            let mut db = super::database(cipher, "clients/acme", &passwords);
+            // Keep the test fast
+            db.config.kdf_config = KdfConfig::Argon2id {
+                iterations: 1,
+                memory: 64 * 1024,
+                parallelism: 1,
+                version: argon2::Version::Version13,
+            };
+
+            let file = NamedTempFile::new()?;
+            super::write(File::create(file.path())?, &db, "keybear")?;
+
+            let db = import::kdbx::open(file.path(), Some("keybear"), None)?;
+            let records = import::kdbx::records(&db)
+                .into_iter()
+                .map(|(_, record)| record)
+                .collect::<Result<Vec<_>>>()?;
+
+            assert_eq!(records.len(), 2);
+            assert_eq!(records[0].name, "ci/token");
+            assert_eq!(records[0].email.as_deref(), Some("ci@example.com"));
+            assert_eq!(
+                records[0].website.as_deref(),
+                Some("https://ci.example.com")
+            );
+            assert_eq!(records[0].password, "hunter2");
+            assert_eq!(records[1].name, "mail");
+            assert_eq!(records[1].email, None);
+            assert_eq!(records[1].password, "correct horse");
+
+            // Another implementation can read it too
+            independent_read(file.path(), cipher)?;
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn samples() -> Result<()> {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/data");
+
+        independent_read(&dir.join("export-aes.kdbx"), Cipher::Aes)?;
+        independent_read(&dir.join("export-chacha20.kdbx"), Cipher::ChaCha20)?;
+
+        Ok(())
+    }
+
+    /// Open an exported database with kdbx-rs instead of the crate that wrote it.
+    fn independent_read(path: &Path, cipher: Cipher) -> Result<()> {
+        let kdbx = kdbx_rs::open(path)?;
+        assert_eq!(
+            kdbx.header().cipher,
+            match cipher {
+                Cipher::Aes => kdbx_rs::binary::Cipher::Aes256,
+                Cipher::ChaCha20 => kdbx_rs::binary::Cipher::ChaCha20,
+            }
+        );
+
+        let kdbx = kdbx
+            .unlock(&kdbx_rs::CompositeKey::from_password("keybear"))
+            .map_err(|err| err.1)?;
+        let root = kdbx.database().root();
+        assert_eq!(root.name(), "keybear");
+
+        let ci = root
+            .find_group(|group| group.name() == "ci")
+            .expect("Group ci exists");
+        let token = ci.entries().next().expect("Entry token exists");
+        assert_eq!(token.title(), Some("token"));
+        assert_eq!(token.username(), Some("ci@example.com"));
+        assert_eq!(token.url(), Some("https://ci.example.com"));
+        assert_eq!(token.password(), Some("hunter2"));
+
+        let mail = root.entries().next().expect("Entry mail exists");
+        assert_eq!(mail.title(), Some("mail"));
+        assert_eq!(mail.password(), Some("correct horse"));
+
+        Ok(())
+    }
+}
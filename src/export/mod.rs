@@ -0,0 +1,203 @@
+pub mod kdbx;
+
+use crate::tree::{self, SEPARATOR};
+use anyhow::{bail, Error, Result};
+use serde::Serialize;
+use std::{collections::HashMap, io::Write, str::FromStr};
+
+/// A password with everything that's known about it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Exported {
+    /// ID of the password on the server.
+    pub id: String,
+    /// Name of the password.
+    pub name: String,
+    /// Associated e-mail.
+    pub email: Option<String>,
+    /// Associated website.
+    pub website: Option<String>,
+    /// The actual password.
+    pub password: String,
+}
+
+/// A password without an e-mail, used by the tests.
+#[cfg(test)]
+pub fn exported(id: &str, name: &str, website: Option<&str>, password: &str) -> Exported {
+    Exported {
+        id: id.to_string(),
+        name: name.to_string(),
+        email: None,
+        website: website.map(String::from),
+        password: password.to_string(),
+    }
+}
+
+/// Formats the passwords can be exported to without encryption.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Plaintext {
+    /// A JSON array of objects with all fields.
+    Json,
+    /// CSV with a `name`, `email`, `website` and `password` column.
+    Csv,
+    /// `KEY="password"` lines, the key is the name relative to the prefix in uppercase.
+    Dotenv,
+}
+
+impl FromStr for Plaintext {
+    type Err = Error;
+
+    fn from_str(format: &str) -> Result<Self> {
+        match format {
+            "json" => Ok(Plaintext::Json),
+            "csv" => Ok(Plaintext::Csv),
+            "dotenv" => Ok(Plaintext::Dotenv),
+            other => bail!("Unknown export format \"{}\"", other),
+        }
+    }
+}
+
+impl Plaintext {
+    /// Write the passwords, names in dotenv files are relative to the prefix.
+    pub fn write<W>(self, mut writer: W, prefix: &str, passwords: &[Exported]) -> Result<()>
+    where
+        W: Write,
+    {
+        match self {
+            Plaintext::Json => {
+                serde_json::to_writer_pretty(&mut writer, passwords)?;
+                writeln!(writer)?;
+            }
+            Plaintext::Csv => {
+                let mut writer = ::csv::Writer::from_writer(writer);
+                writer.write_record(["name", "email", "website", "password"])?;
+                for password in passwords {
+                    writer.write_record([
+                        &password.name,
+                        password.email.as_deref().unwrap_or_default(),
+                        password.website.as_deref().unwrap_or_default(),
+                        &password.password,
+                    ])?;
+                }
+                writer.flush()?;
+            }
+            Plaintext::Dotenv => {
+                // Different names can become the same key
+                let mut keys = HashMap::new();
+                for password in passwords {
+                    let key = dotenv_key(relative(prefix, &password.name));
+                    if let Some(other) = keys.insert(key.clone(), &password.name) {
+                        bail!(
+                            "Passwords \"{}\" and \"{}\" both become {}",
+                            other,
+                            password.name,
+                            key
+                        );
+                    }
+
+                    writeln!(writer, "{}=\"{}\"", key, dotenv_escape(&password.password))?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The name relative to the prefix folder, the full name when it's the folder itself.
+fn relative<'a>(prefix: &str, name: &'a str) -> &'a str {
+    let name = tree::folder(name);
+
+    match name
+        .strip_prefix(tree::folder(prefix))
+        .map(|relative| relative.trim_start_matches(SEPARATOR))
+    {
+        Some(relative) if !relative.is_empty() => relative,
+        _ => name,
+    }
+}
+
+/// Turn a name into an environment variable name, like `aws/prod-key` into `AWS_PROD_KEY`.
+fn dotenv_key(name: &str) -> String {
+    let key = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+
+    // Variables can't start with a digit
+    if key.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{}", key)
+    } else {
+        key
+    }
+}
+
+/// Escape a value for between double quotes.
+fn dotenv_escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Exported, Plaintext};
+    use anyhow::Result;
+
+    fn exported(name: &str, password: &str) -> Exported {
+        super::exported("1", name, Some("https://example.com"), password)
+    }
+
+    fn write(format: Plaintext, prefix: &str, passwords: &[Exported]) -> Result<String> {
+        let mut output = Vec::new();
+        format.write(&mut output, prefix, passwords)?;
+
+        Ok(String::from_utf8(output)?)
+    }
+
+    #[test]
+    fn csv() -> Result<()> {
+        assert_eq!(
+            write(Plaintext::Csv, "", &[exported("ci/token", "pass,word")])?,
+            "name,email,website,password\nci/token,,https://example.com,\"pass,word\"\n"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn dotenv() -> Result<()> {
+        assert_eq!(
+            write(
+                Plaintext::Dotenv,
+                "ci/",
+                &[
+                    exported("ci/deploy-token", "hunter2"),
+                    exported("ci/aws/1st key", "a\"b\\c\nd"),
+                ]
+            )?,
+            "DEPLOY_TOKEN=\"hunter2\"\nAWS_1ST_KEY=\"a\\\"b\\\\c\\nd\"\n"
+        );
+        assert_eq!(
+            write(Plaintext::Dotenv, "", &[exported("1password", "x")])?,
+            "_1PASSWORD=\"x\"\n"
+        );
+
+        // Names that become the same key are an error
+        assert!(write(
+            Plaintext::Dotenv,
+            "",
+            &[exported("a-b", "x"), exported("a/b", "y")]
+        )
+        .is_err());
+
+        Ok(())
+    }
+}
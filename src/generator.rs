@@ -0,0 +1,491 @@
+use anyhow::{anyhow, bail, ensure, Error, Result};
+use chbs::{
+    component::{
+        phrase::BasicPhraseBuilder,
+        word::{FixedWordSetProvider, WordCapitalizer},
+    },
+    entropy::Entropy,
+    prelude::*,
+    probability::Probability,
+    scheme::{Scheme, SchemeBuilder},
+    word::WordList,
+};
+use rand::{rngs::OsRng, seq::SliceRandom};
+use serde::Deserialize;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// Default amount of words in a pass-phrase.
+pub const DEFAULT_WORDS: usize = 5;
+/// Default separator between the words of a pass-phrase.
+pub const DEFAULT_SEPARATOR: &str = " ";
+/// Default amount of characters in a password of random characters.
+pub const DEFAULT_CHARACTERS: usize = 24;
+/// Default minimum amount of characters from every class.
+pub const DEFAULT_MIN_PER_CLASS: usize = 1;
+
+/// Lowercase letters.
+const LOWER: &str = "abcdefghijklmnopqrstuvwxyz";
+/// Uppercase letters.
+const UPPER: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+/// Digits.
+const DIGITS: &str = "0123456789";
+/// Printable ASCII symbols.
+const SYMBOLS: &str = "!\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~";
+/// Characters that are easily mistaken for each other.
+const LOOK_ALIKES: &str = "Il1|O0o";
+
+/// The way a password is generated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Generator {
+    /// A pass-phrase of random words.
+    Passphrase(Passphrase),
+    /// Random characters.
+    Characters(Characters),
+}
+
+impl Generator {
+    /// Generate a new password.
+    pub fn generate(&self) -> Result<String> {
+        match self {
+            Generator::Passphrase(passphrase) => Ok(passphrase.scheme()?.generate()),
+            Generator::Characters(characters) => characters.generate(),
+        }
+    }
+
+    /// The entropy of the generated passwords in bits.
+    pub fn entropy(&self) -> Result<f64> {
+        match self {
+            Generator::Passphrase(passphrase) => Ok(passphrase.scheme()?.entropy().bits()),
+            Generator::Characters(characters) => characters.entropy(),
+        }
+    }
+}
+
+/// How the words of a pass-phrase are capitalized.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Capitalization {
+    /// Keep all words lowercase.
+    None,
+    /// Capitalize the first letter of every word.
+    First,
+    /// Capitalize whole words.
+    Upper,
+    /// Capitalize the first letter of random words.
+    #[default]
+    Random,
+}
+
+impl Capitalization {
+    /// The word styler applying the capitalization.
+    fn styler(self) -> WordCapitalizer {
+        match self {
+            Capitalization::None => WordCapitalizer::new(Probability::Never, Probability::Never),
+            Capitalization::First => WordCapitalizer::new(Probability::Always, Probability::Never),
+            Capitalization::Upper => WordCapitalizer::new(Probability::Never, Probability::Always),
+            Capitalization::Random => WordCapitalizer::new(Probability::half(), Probability::Never),
+        }
+    }
+}
+
+impl FromStr for Capitalization {
+    type Err = Error;
+
+    fn from_str(capitalization: &str) -> Result<Self> {
+        match capitalization {
+            "none" => Ok(Capitalization::None),
+            "first" => Ok(Capitalization::First),
+            "upper" => Ok(Capitalization::Upper),
+            "random" => Ok(Capitalization::Random),
+            other => bail!("Unknown capitalization \"{}\"", other),
+        }
+    }
+}
+
+/// Something that's appended to a pass-phrase.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Append {
+    /// A random digit.
+    Number,
+    /// A random symbol.
+    Symbol,
+}
+
+impl Append {
+    /// The characters one is picked from.
+    fn characters(self) -> &'static str {
+        match self {
+            Append::Number => DIGITS,
+            Append::Symbol => SYMBOLS,
+        }
+    }
+}
+
+impl FromStr for Append {
+    type Err = Error;
+
+    fn from_str(append: &str) -> Result<Self> {
+        match append {
+            "number" => Ok(Append::Number),
+            "symbol" => Ok(Append::Symbol),
+            other => bail!("Unknown value to append \"{}\"", other),
+        }
+    }
+}
+
+impl HasEntropy for Append {
+    fn entropy(&self) -> Entropy {
+        Entropy::from_real(self.characters().chars().count() as f64)
+    }
+}
+
+impl PhraseStyler for Append {
+    fn style_phrase(&self, mut phrase: String) -> String {
+        let characters = self.characters().chars().collect::<Vec<_>>();
+        if let Some(c) = characters.choose(&mut OsRng) {
+            phrase.push(*c);
+        }
+
+        phrase
+    }
+}
+
+/// Settings for generating a pass-phrase from random words.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Passphrase {
+    /// Amount of words.
+    pub words: usize,
+    /// What's put between the words.
+    pub separator: String,
+    /// How the words are capitalized.
+    pub capitalize: Capitalization,
+    /// What's appended after the words.
+    pub append: Vec<Append>,
+    /// File with a word on every line to use instead of the EFF long list, dice numbers in front
+    /// of the words are ignored.
+    pub wordlist: Option<PathBuf>,
+}
+
+impl Passphrase {
+    /// Build the scheme to generate pass-phrases with.
+    fn scheme(&self) -> Result<Scheme> {
+        ensure!(self.words > 0, "Pass-phrase must contain at least 1 word");
+
+        let wordlist = match &self.wordlist {
+            Some(path) => load_wordlist(path)?,
+            None => WordList::default(),
+        };
+
+        SchemeBuilder::default()
+            .word_set_provider(Box::new(FixedWordSetProvider::new(
+                wordlist.sampler(),
+                self.words,
+            )))
+            .word_stylers(vec![Box::new(self.capitalize.styler())])
+            .phrase_builder(Box::new(BasicPhraseBuilder::new(self.separator.clone())))
+            .phrase_stylers(
+                self.append
+                    .iter()
+                    .map(|append| Box::new(*append) as Box<dyn PhraseStyler>)
+                    .collect(),
+            )
+            .build()
+            .map_err(|err| anyhow!("Invalid pass-phrase settings: {}", err))
+    }
+}
+
+/// Load a wordlist with a word on every line, ignoring dice numbers and duplicate words.
+///
+/// Duplicates would make the entropy look higher than it is.
+fn load_wordlist(path: &Path) -> Result<WordList> {
+    let mut words = fs::read_to_string(path)
+        .map_err(|err| anyhow!("Could not load wordlist {:?}: {}", path, err))?
+        .lines()
+        .filter_map(|line| line.split_whitespace().last())
+        .map(String::from)
+        .collect::<Vec<_>>();
+    words.sort_unstable();
+    words.dedup();
+    ensure!(
+        !words.is_empty(),
+        "Wordlist {:?} doesn't contain any words",
+        path
+    );
+
+    Ok(WordList::new(words))
+}
+
+impl Default for Passphrase {
+    fn default() -> Self {
+        Self {
+            words: DEFAULT_WORDS,
+            separator: DEFAULT_SEPARATOR.to_string(),
+            capitalize: Capitalization::default(),
+            append: Vec::new(),
+            wordlist: None,
+        }
+    }
+}
+
+/// A group of characters that can be included in a generated password.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CharacterClass {
+    /// Lowercase letters.
+    Lower,
+    /// Uppercase letters.
+    Upper,
+    /// Digits.
+    Digits,
+    /// Symbols.
+    Symbols,
+}
+
+impl CharacterClass {
+    /// All character classes.
+    pub const ALL: [CharacterClass; 4] = [
+        CharacterClass::Lower,
+        CharacterClass::Upper,
+        CharacterClass::Digits,
+        CharacterClass::Symbols,
+    ];
+
+    /// The characters belonging to this class.
+    fn characters(self) -> &'static str {
+        match self {
+            CharacterClass::Lower => LOWER,
+            CharacterClass::Upper => UPPER,
+            CharacterClass::Digits => DIGITS,
+            CharacterClass::Symbols => SYMBOLS,
+        }
+    }
+}
+
+impl FromStr for CharacterClass {
+    type Err = Error;
+
+    fn from_str(class: &str) -> Result<Self> {
+        match class {
+            "lower" => Ok(CharacterClass::Lower),
+            "upper" => Ok(CharacterClass::Upper),
+            "digits" => Ok(CharacterClass::Digits),
+            "symbols" => Ok(CharacterClass::Symbols),
+            other => bail!("Unknown character class \"{}\"", other),
+        }
+    }
+}
+
+/// Settings for generating a password from random characters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Characters {
+    /// Total amount of characters.
+    pub length: usize,
+    /// The classes to pick characters from, ignored when an alphabet is set.
+    pub classes: Vec<CharacterClass>,
+    /// Minimum amount of characters from each class.
+    pub min_per_class: usize,
+    /// Don't use characters that look alike.
+    pub exclude_look_alikes: bool,
+    /// Custom set of characters to pick from.
+    pub alphabet: Option<String>,
+}
+
+impl Characters {
+    /// Generate a password with a cryptographically secure random number generator.
+    pub fn generate(&self) -> Result<String> {
+        ensure!(self.length > 0, "Password length must be at least 1");
+
+        let pools = self.pools()?;
+
+        // The minimum only applies to the classes
+        let min_per_class = if self.alphabet.is_some() {
+            0
+        } else {
+            self.min_per_class
+        };
+        let required = min_per_class.checked_mul(pools.len()).ok_or_else(|| {
+            anyhow!(
+                "A minimum of {} characters of {} classes is too large",
+                min_per_class,
+                pools.len()
+            )
+        })?;
+        ensure!(
+            required <= self.length,
+            "A length of {} is too short to contain {} characters of {} classes",
+            self.length,
+            min_per_class,
+            pools.len()
+        );
+
+        let mut rng = OsRng;
+
+        // Pick the required characters from every class
+        let mut password = pools
+            .iter()
+            .flat_map(|pool| (0..min_per_class).map(move |_| pool))
+            .map(|pool| pool.choose(&mut rng))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| anyhow!("Could not pick a character"))?;
+
+        // Fill the rest with characters from all classes
+        let all = pools.concat();
+        while password.len() < self.length {
+            password.push(
+                all.choose(&mut rng)
+                    .ok_or_else(|| anyhow!("Could not pick a character"))?,
+            );
+        }
+
+        // Don't let the required characters always be at the start
+        password.shuffle(&mut rng);
+
+        Ok(password.into_iter().collect())
+    }
+
+    /// The entropy in bits, not taking the minimum per class into account.
+    pub fn entropy(&self) -> Result<f64> {
+        let characters = self.pools()?.concat().len();
+
+        Ok(self.length as f64 * (characters as f64).log2())
+    }
+
+    /// The characters to pick from for every class, or for the alphabet.
+    fn pools(&self) -> Result<Vec<Vec<char>>> {
+        // Every class becomes a pool of characters to pick from
+        let pools = match &self.alphabet {
+            Some(alphabet) => vec![self.pool(alphabet)],
+            None => self
+                .classes
+                .iter()
+                .map(|class| self.pool(class.characters()))
+                .collect(),
+        };
+        ensure!(
+            !pools.is_empty(),
+            "No characters to generate a password from"
+        );
+        if let Some(empty) = pools.iter().position(|pool| pool.is_empty()) {
+            bail!(
+                "No characters left in {} to generate a password from",
+                self.alphabet
+                    .as_ref()
+                    .map(|_| "the alphabet".to_string())
+                    .unwrap_or_else(|| format!("class {:?}", self.classes[empty]))
+            );
+        }
+
+        Ok(pools)
+    }
+
+    /// The unique characters of a set, without look-alikes when they are excluded.
+    fn pool(&self, characters: &str) -> Vec<char> {
+        let mut pool = characters
+            .chars()
+            .filter(|c| !self.exclude_look_alikes || !LOOK_ALIKES.contains(*c))
+            .collect::<Vec<_>>();
+        pool.sort_unstable();
+        pool.dedup();
+
+        pool
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Append, Capitalization, CharacterClass, Characters, Passphrase, LOOK_ALIKES};
+    use anyhow::Result;
+    use std::fs;
+
+    #[test]
+    fn passphrase() -> Result<()> {
+        // A diced wordlist, the duplicate doesn't count
+        let dir = tempfile::tempdir()?;
+        let wordlist = dir.path().join("wordlist.txt");
+        fs::write(&wordlist, "11111\talpha\n11112\tbeta\n11113\tbeta\n")?;
+
+        let passphrase = Passphrase {
+            words: 4,
+            separator: "-".to_string(),
+            capitalize: Capitalization::Upper,
+            append: vec![Append::Number, Append::Symbol],
+            wordlist: Some(wordlist),
+        };
+
+        let password = passphrase.scheme()?.generate();
+        let (words, appended) = password.split_at(password.len() - 2);
+        assert!(words
+            .split('-')
+            .all(|word| word == "ALPHA" || word == "BETA"));
+        assert_eq!(words.split('-').count(), 4);
+        assert!(appended.chars().next().unwrap().is_ascii_digit());
+        assert!(appended.chars().nth(1).unwrap().is_ascii_punctuation());
+
+        // 4 words of 1 bit, a digit and a symbol
+        let entropy = passphrase.scheme()?.entropy().bits();
+        assert!((entropy - (4.0 + 10f64.log2() + 32f64.log2())).abs() < 0.01);
+
+        // The default is the EFF long list
+        let entropy = Passphrase::default().scheme()?.entropy().bits();
+        assert!(entropy > 60.0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn characters() -> Result<()> {
+        let characters = Characters {
+            length: 16,
+            classes: CharacterClass::ALL.to_vec(),
+            min_per_class: 2,
+            exclude_look_alikes: true,
+            alphabet: None,
+        };
+
+        for _ in 0..100 {
+            let password = characters.generate()?;
+            assert_eq!(password.chars().count(), 16);
+            assert!(password.chars().filter(char::is_ascii_lowercase).count() >= 2);
+            assert!(password.chars().filter(char::is_ascii_uppercase).count() >= 2);
+            assert!(password.chars().filter(char::is_ascii_digit).count() >= 2);
+            assert!(password.chars().filter(char::is_ascii_punctuation).count() >= 2);
+            assert!(!password.chars().any(|c| LOOK_ALIKES.contains(c)));
+        }
+
+        // A custom alphabet replaces the classes
+        let password = Characters {
+            alphabet: Some("ab".to_string()),
+            ..characters.clone()
+        }
+        .generate()?;
+        assert!(password.chars().all(|c| c == 'a' || c == 'b'));
+
+        // The minimum can't exceed the length
+        assert!(Characters {
+            length: 7,
+            ..characters.clone()
+        }
+        .generate()
+        .is_err());
+        assert!(Characters {
+            min_per_class: usize::MAX,
+            ..characters.clone()
+        }
+        .generate()
+        .is_err());
+
+        // Excluding all characters is an error
+        assert!(Characters {
+            alphabet: Some("0O".to_string()),
+            ..characters
+        }
+        .generate()
+        .is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,349 @@
+use crate::{
+    file::create_private_file,
+    import::{email_from_username, host, Record},
+};
+use ::csv::{ReaderBuilder, StringRecord, WriterBuilder};
+use anyhow::{anyhow, bail, Error, Result};
+use std::{io::Read, path::Path, str::FromStr};
+
+/// Password managers with a known CSV export layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Preset {
+    /// Bitwarden.
+    Bitwarden,
+    /// Chrome and Chromium.
+    Chrome,
+    /// Firefox, the entries have no name so the host of the website is used.
+    Firefox,
+    /// LastPass.
+    LastPass,
+}
+
+impl Preset {
+    /// All presets in the order they are detected.
+    const ALL: [Preset; 4] = [
+        Preset::Bitwarden,
+        Preset::LastPass,
+        Preset::Chrome,
+        Preset::Firefox,
+    ];
+
+    /// Columns that identify the export.
+    fn columns(self) -> &'static [&'static str] {
+        match self {
+            Preset::Bitwarden => &[
+                "folder",
+                "name",
+                "login_uri",
+                "login_username",
+                "login_password",
+            ],
+            Preset::Chrome => &["name", "url", "username", "password"],
+            Preset::Firefox => &["url", "username", "password", "httpRealm"],
+            Preset::LastPass => &["url", "username", "password", "name", "grouping"],
+        }
+    }
+
+    /// Find the preset matching the columns of a file.
+    pub fn detect(headers: &StringRecord) -> Option<Self> {
+        Self::ALL.iter().copied().find(|preset| {
+            preset
+                .columns()
+                .iter()
+                .all(|column| headers.iter().any(|header| header == *column))
+        })
+    }
+}
+
+impl FromStr for Preset {
+    type Err = Error;
+
+    fn from_str(preset: &str) -> Result<Self> {
+        match preset {
+            "bitwarden" => Ok(Preset::Bitwarden),
+            "chrome" | "chromium" => Ok(Preset::Chrome),
+            "firefox" => Ok(Preset::Firefox),
+            "lastpass" => Ok(Preset::LastPass),
+            other => bail!("Unknown CSV preset \"{}\"", other),
+        }
+    }
+}
+
+/// Which column holds which field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mapping {
+    /// Name of the password, the host of the website is used when not set.
+    pub name: Option<String>,
+    /// Folder that's put in front of the name.
+    pub folder: Option<String>,
+    /// The actual password.
+    pub password: String,
+    /// Associated e-mail, values that aren't e-mail addresses are ignored.
+    pub email: Option<String>,
+    /// Associated website.
+    pub website: Option<String>,
+}
+
+impl From<Preset> for Mapping {
+    fn from(preset: Preset) -> Self {
+        let column = |column: &str| Some(column.to_string());
+
+        match preset {
+            Preset::Bitwarden => Self {
+                name: column("name"),
+                folder: column("folder"),
+                password: "login_password".to_string(),
+                email: column("login_username"),
+                website: column("login_uri"),
+            },
+            Preset::Chrome => Self {
+                name: column("name"),
+                folder: None,
+                password: "password".to_string(),
+                email: column("username"),
+                website: column("url"),
+            },
+            Preset::Firefox => Self {
+                name: None,
+                folder: None,
+                password: "password".to_string(),
+                email: column("username"),
+                website: column("url"),
+            },
+            Preset::LastPass => Self {
+                name: column("name"),
+                folder: column("grouping"),
+                password: "password".to_string(),
+                email: column("username"),
+                website: column("url"),
+            },
+        }
+    }
+}
+
+impl FromStr for Mapping {
+    type Err = Error;
+
+    /// Parse a mapping like `name=title,password=pass,website=url,email=username`.
+    fn from_str(mapping: &str) -> Result<Self> {
+        let mut name = None;
+        let mut folder = None;
+        let mut password = None;
+        let mut email = None;
+        let mut website = None;
+
+        for pair in mapping.split(',').filter(|pair| !pair.trim().is_empty()) {
+            let (field, column) = pair
+                .split_once('=')
+                .ok_or_else(|| anyhow!("Mapping \"{}\" is not like field=column", pair))?;
+            let column = Some(column.trim().to_string());
+
+            match field.trim() {
+                "name" => name = column,
+                "folder" => folder = column,
+                "password" => password = column,
+                "email" => email = column,
+                "website" => website = column,
+                other => bail!(
+                    "Unknown field \"{}\", use name, folder, password, email or website",
+                    other
+                ),
+            }
+        }
+
+        Ok(Self {
+            name,
+            folder,
+            password: password.ok_or_else(|| anyhow!("The mapping needs a password column"))?,
+            email,
+            website,
+        })
+    }
+}
+
+/// Rows of a CSV file.
+#[derive(Debug)]
+pub struct Table {
+    /// The header row.
+    pub headers: StringRecord,
+    /// All other rows with the line they start at.
+    pub rows: Vec<(u64, StringRecord)>,
+}
+
+impl Table {
+    /// Parse a CSV file with a header row, quoted fields can contain newlines.
+    pub fn read<R>(reader: R) -> Result<Self>
+    where
+        R: Read,
+    {
+        let mut reader = ReaderBuilder::new().flexible(true).from_reader(reader);
+
+        let headers = reader.headers()?.clone();
+        let rows = reader
+            .records()
+            .map(|row| {
+                let row = row?;
+                let line = row.position().map_or(0, |position| position.line());
+
+                Ok((line, row))
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self { headers, rows })
+    }
+
+    /// Turn every row into a password using the mapping.
+    pub fn records(&self, mapping: &Mapping) -> Result<Vec<Result<Record>>> {
+        let index = |column: &str| {
+            self.headers
+                .iter()
+                .position(|header| header == column)
+                .ok_or_else(|| anyhow!("Column \"{}\" not found in the CSV file", column))
+        };
+        let optional = |column: &Option<String>| column.as_deref().map(index).transpose();
+
+        let name = optional(&mapping.name)?;
+        let folder = optional(&mapping.folder)?;
+        let password = index(&mapping.password)?;
+        let email = optional(&mapping.email)?;
+        let website = optional(&mapping.website)?;
+
+        Ok(self
+            .rows
+            .iter()
+            .map(|(_, row)| {
+                // Metadata is trimmed, the password is kept as it is
+                let field = |index: Option<usize>| {
+                    index
+                        .and_then(|index| row.get(index))
+                        .map(str::trim)
+                        .filter(|value| !value.is_empty())
+                };
+
+                let website = field(website).map(String::from);
+                let name = match field(name) {
+                    Some(name) => name.to_string(),
+                    None => website
+                        .as_deref()
+                        .and_then(host)
+                        .ok_or_else(|| anyhow!("Row has no name and no website"))?,
+                };
+                let name = match field(folder) {
+                    // LastPass separates nested folders with backslashes
+                    Some(folder) => format!("{}/{}", folder.replace('\\', "/"), name),
+                    None => name,
+                };
+
+                Ok(Record {
+                    name,
+                    // Spaces can be part of the password
+                    password: row.get(password).unwrap_or_default().to_string(),
+                    email: field(email).and_then(email_from_username),
+                    website,
+                    skipped: Vec::new(),
+                })
+            })
+            .collect())
+    }
+
+    /// Write the rows with the error that made them fail to a new file only the user can read.
+    pub fn write_report(&self, path: &Path, failed: &[(usize, String)]) -> Result<()> {
+        let file = create_private_file(path)
+            .map_err(|err| anyhow!("Could not create report file {:?}: {}", path, err))?;
+
+        let mut writer = WriterBuilder::new().flexible(true).from_writer(file);
+        writer.write_record(self.headers.iter().chain(Some("error")))?;
+        for (index, error) in failed {
+            let (_, row) = &self.rows[*index];
+            writer.write_record(row.iter().chain(Some(error.as_str())))?;
+        }
+        writer.flush()?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Mapping, Preset, Table};
+    use crate::import::Record;
+    use anyhow::Result;
+
+    #[test]
+    fn presets() -> Result<()> {
+        let table = Table::read(
+            "folder,favorite,type,name,notes,fields,reprompt,login_uri,login_username,login_password,login_totp
+work,,login,mail,\"multi
+line note\",,0,https://mail.work.com,me@work.com,\"pass,word\",
+,,login,github,,,0,github.com,me,hunter2,
+"
+            .as_bytes(),
+        )?;
+        assert_eq!(Preset::detect(&table.headers), Some(Preset::Bitwarden));
+        assert_eq!(table.rows[1].0, 4);
+
+        let records = table
+            .records(&Mapping::from(Preset::Bitwarden))?
+            .into_iter()
+            .collect::<Result<Vec<_>>>()?;
+        assert_eq!(
+            records,
+            [
+                Record {
+                    name: "work/mail".to_string(),
+                    password: "pass,word".to_string(),
+                    email: Some("me@work.com".to_string()),
+                    website: Some("https://mail.work.com".to_string()),
+                    skipped: Vec::new(),
+                },
+                Record {
+                    name: "github".to_string(),
+                    password: "hunter2".to_string(),
+                    email: None,
+                    website: Some("github.com".to_string()),
+                    skipped: Vec::new(),
+                },
+            ]
+        );
+
+        // Firefox has no names
+        let table = Table::read(
+            "\"url\",\"username\",\"password\",\"httpRealm\",\"formActionOrigin\",\"guid\"
+\"https://accounts.example.com\",\"me@home.com\",\"hunter2\",,\"\",\"{1}\"
+"
+            .as_bytes(),
+        )?;
+        assert_eq!(Preset::detect(&table.headers), Some(Preset::Firefox));
+        let record = table.records(&Mapping::from(Preset::Firefox))?.remove(0)?;
+        assert_eq!(record.name, "accounts.example.com");
+
+        Ok(())
+    }
+
+    #[test]
+    fn mapping() -> Result<()> {
+        let mapping = "name=title, password=pass,website=url,email=username".parse::<Mapping>()?;
+        assert_eq!(mapping.name.as_deref(), Some("title"));
+        assert_eq!(mapping.password, "pass");
+
+        let table =
+            Table::read("title,pass,url,username\nmail,hunter2,,\n spaced , pass ,,\n".as_bytes())?;
+        assert_eq!(Preset::detect(&table.headers), None);
+        let mut records = table.records(&mapping)?;
+        assert_eq!(records.remove(0)?.name, "mail");
+
+        // Only the metadata is trimmed
+        let record = records.remove(0)?;
+        assert_eq!(record.name, "spaced");
+        assert_eq!(record.password, " pass ");
+
+        // A missing column is an error for the whole file
+        let table = Table::read("title,url\nmail,example.com\n".as_bytes())?;
+        assert!(table.records(&mapping).is_err());
+
+        assert!("name=title".parse::<Mapping>().is_err());
+        assert!("username=login,password=pass".parse::<Mapping>().is_err());
+
+        Ok(())
+    }
+}
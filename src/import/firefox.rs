@@ -0,0 +1,433 @@
+use crate::import::{email_from_username, host, Record};
+use aes::{
+    cipher::{block_padding::Pkcs7, BlockDecryptMut, KeyIvInit},
+    Aes256,
+};
+use anyhow::{anyhow, bail, ensure, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use des::TdesEde3;
+use hmac::{Hmac, Mac};
+use rusqlite::{Connection, OpenFlags};
+use serde::Deserialize;
+use sha1::{Digest, Sha1};
+use sha2::Sha256;
+use std::{convert::TryFrom, fs, path::Path};
+
+/// File with the encrypted logins.
+const LOGINS_FILENAME: &str = "logins.json";
+/// NSS database with the key the logins are encrypted with.
+const KEY_DB_FILENAME: &str = "key4.db";
+/// ID NSS gives the key used for the logins.
+const KEY_ID: [u8; 16] = [0xf8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1];
+/// Text that's encrypted with the primary password to check it.
+const PASSWORD_CHECK: &[u8] = b"password-check";
+
+/// DER encoded object identifiers of the algorithms NSS uses.
+mod oid {
+    /// PKCS #5 password based encryption scheme 2.
+    pub const PBES2: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x05, 0x0d];
+    /// PKCS #5 key derivation function 2.
+    pub const PBKDF2: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x05, 0x0c];
+    /// HMAC with SHA-256 as the pseudorandom function of PBKDF2.
+    pub const HMAC_SHA256: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x02, 0x09];
+    /// PKCS #12 password based encryption with SHA-1 and triple DES, used by older NSS versions.
+    pub const PBE_SHA1_3DES: &[u8] = &[
+        0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x0c, 0x05, 0x01, 0x03,
+    ];
+    /// AES-256 in CBC mode.
+    pub const AES256_CBC: &[u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x01, 0x2a];
+    /// Triple DES in CBC mode.
+    pub const DES_EDE3_CBC: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x03, 0x07];
+}
+
+/// The file with the logins of a Firefox profile.
+#[derive(Debug, Deserialize)]
+struct Logins {
+    /// All saved logins.
+    logins: Vec<Login>,
+}
+
+/// A saved login, the username and password are encrypted.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Login {
+    /// Website the login is for, like `https://example.com`.
+    hostname: String,
+    /// Base64 encoded encrypted username.
+    encrypted_username: String,
+    /// Base64 encoded encrypted password.
+    encrypted_password: String,
+}
+
+/// Get the key the logins of the profile are encrypted with.
+///
+/// Returns `None` when the primary password is wrong, an empty password is used when the profile
+/// has no primary password.
+pub fn key(profile: &Path, primary_password: &str) -> Result<Option<Vec<u8>>> {
+    let path = profile.join(KEY_DB_FILENAME);
+    let db = Connection::open_with_flags(&path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|err| anyhow!("Could not open {:?}: {}", path, err))?;
+
+    // Check the primary password first, decrypting the key with a wrong one gives garbage
+    let (global_salt, check) = db
+        .query_row(
+            "SELECT item1, item2 FROM metadata WHERE id = 'password'",
+            [],
+            |row| Ok((row.get::<_, Vec<u8>>(0)?, row.get::<_, Vec<u8>>(1)?)),
+        )
+        .map_err(|err| anyhow!("Could not read password metadata from {:?}: {}", path, err))?;
+    match decrypt_pbe(&check, &global_salt, primary_password.as_bytes()) {
+        Ok(check) if check == PASSWORD_CHECK => (),
+        _ => return Ok(None),
+    }
+
+    let mut statement = db.prepare("SELECT a11, a102 FROM nssPrivate")?;
+    let mut rows = statement.query([])?;
+    while let Some(row) = rows.next()? {
+        let id = row.get::<_, Option<Vec<u8>>>(1)?;
+        if id.as_deref() != Some(&KEY_ID[..]) {
+            continue;
+        }
+
+        let encrypted = row.get::<_, Vec<u8>>(0)?;
+
+        return decrypt_pbe(&encrypted, &global_salt, primary_password.as_bytes()).map(Some);
+    }
+
+    bail!("No key for the logins found in {:?}", path)
+}
+
+/// Read and decrypt all logins of the profile.
+///
+/// Every login is returned with its website, a login that can't be decrypted is an error for
+/// that login only.
+pub fn read(profile: &Path, key: &[u8]) -> Result<Vec<(String, Result<Record>)>> {
+    let path = profile.join(LOGINS_FILENAME);
+    let logins: Logins = serde_json::from_str(
+        &fs::read_to_string(&path).map_err(|err| anyhow!("Could not read {:?}: {}", path, err))?,
+    )
+    .map_err(|err| anyhow!("Could not parse {:?}: {}", path, err))?;
+
+    Ok(logins
+        .logins
+        .into_iter()
+        .map(|login| {
+            let record = record(&login, key);
+
+            (login.hostname, record)
+        })
+        .collect())
+}
+
+/// Turn a login into a password.
+fn record(login: &Login, key: &[u8]) -> Result<Record> {
+    let username = decrypt_login(&login.encrypted_username, key)
+        .map_err(|err| anyhow!("Could not decrypt username: {}", err))?;
+    let password = decrypt_login(&login.encrypted_password, key)
+        .map_err(|err| anyhow!("Could not decrypt password: {}", err))?;
+
+    Ok(Record {
+        name: host(&login.hostname).ok_or_else(|| anyhow!("Login has no valid website"))?,
+        password,
+        email: email_from_username(&username),
+        website: Some(login.hostname.clone()),
+        skipped: Vec::new(),
+    })
+}
+
+/// Decrypt a base64 encoded username or password with the key from the key database.
+fn decrypt_login(encrypted: &str, key: &[u8]) -> Result<String> {
+    let bytes = STANDARD.decode(encrypted)?;
+
+    // SEQUENCE { key ID, SEQUENCE { cipher, IV }, ciphertext }
+    let fields = Tlv::read_all(&bytes)?.sequence()?;
+    ensure!(fields.len() == 3, "Unexpected encrypted login");
+    ensure!(
+        fields[0].octets()? == KEY_ID,
+        "Encrypted with an unknown key"
+    );
+    let cipher = fields[1].sequence()?;
+    ensure!(cipher.len() == 2, "Unexpected encrypted login");
+    let iv = cipher[1].octets()?;
+    let ciphertext = fields[2].octets()?;
+
+    let decrypted = match cipher[0].oid()? {
+        oid::DES_EDE3_CBC => des3_cbc(key, iv, ciphertext)?,
+        oid::AES256_CBC => aes256_cbc(key, iv, ciphertext)?,
+        _ => bail!("Unsupported login encryption"),
+    };
+
+    String::from_utf8(decrypted).map_err(|_| anyhow!("Decrypted value is not valid UTF-8"))
+}
+
+/// Decrypt an item of the key database with the primary password.
+fn decrypt_pbe(item: &[u8], global_salt: &[u8], primary_password: &[u8]) -> Result<Vec<u8>> {
+    // SEQUENCE { SEQUENCE { algorithm, parameters }, ciphertext }
+    let fields = Tlv::read_all(item)?.sequence()?;
+    ensure!(fields.len() == 2, "Unexpected encrypted item");
+    let algorithm = fields[0].sequence()?;
+    ensure!(algorithm.len() == 2, "Unexpected encryption algorithm");
+    let ciphertext = fields[1].octets()?;
+
+    // NSS hashes the primary password with the global salt before using it
+    let password = Sha1::new()
+        .chain_update(global_salt)
+        .chain_update(primary_password)
+        .finalize();
+
+    match algorithm[0].oid()? {
+        oid::PBES2 => {
+            let parameters = algorithm[1].sequence()?;
+            ensure!(parameters.len() == 2, "Unexpected PBES2 parameters");
+
+            // SEQUENCE { PBKDF2, SEQUENCE { salt, iterations, key length, SEQUENCE { PRF } } }
+            let kdf = parameters[0].sequence()?;
+            ensure!(
+                kdf.len() == 2 && kdf[0].oid()? == oid::PBKDF2,
+                "Unsupported key derivation"
+            );
+            let kdf = kdf[1].sequence()?;
+            ensure!(kdf.len() == 4, "Unexpected PBKDF2 parameters");
+            ensure!(
+                kdf[3].sequence()?.first().map(Tlv::oid).transpose()? == Some(oid::HMAC_SHA256),
+                "Unsupported PBKDF2 hash"
+            );
+            let mut key = vec![0; kdf[2].integer()? as usize];
+            pbkdf2::pbkdf2_hmac::<Sha256>(&password, kdf[0].octets()?, kdf[1].integer()?, &mut key);
+
+            // SEQUENCE { AES-256-CBC, IV }
+            let cipher = parameters[1].sequence()?;
+            ensure!(
+                cipher.len() == 2 && cipher[0].oid()? == oid::AES256_CBC,
+                "Unsupported cipher"
+            );
+            // NSS stores the IV without the header of the octet string that's part of it
+            let iv = cipher[1].octets()?;
+            let iv = if iv.len() == 14 {
+                [&[0x04, 0x0e], iv].concat()
+            } else {
+                iv.to_vec()
+            };
+
+            aes256_cbc(&key, &iv, ciphertext)
+        }
+        oid::PBE_SHA1_3DES => {
+            // SEQUENCE { entry salt, iterations }
+            let parameters = algorithm[1].sequence()?;
+            ensure!(!parameters.is_empty(), "Unexpected PBE parameters");
+            let entry_salt = parameters[0].octets()?;
+
+            let mut padded_salt = entry_salt.to_vec();
+            padded_salt.resize(padded_salt.len().max(20), 0);
+            let hashed = Sha1::new()
+                .chain_update(password)
+                .chain_update(entry_salt)
+                .finalize();
+
+            let hmac = |parts: &[&[u8]]| -> Result<Vec<u8>> {
+                let mut mac = Hmac::<Sha1>::new_from_slice(&hashed)?;
+                parts.iter().for_each(|part| mac.update(part));
+
+                Ok(mac.finalize().into_bytes().to_vec())
+            };
+            let first = hmac(&[&padded_salt, entry_salt])?;
+            let intermediate = hmac(&[&padded_salt])?;
+            let second = hmac(&[&intermediate, entry_salt])?;
+            let key = [first, second].concat();
+
+            des3_cbc(&key[..24], &key[key.len() - 8..], ciphertext)
+        }
+        _ => bail!("Unsupported encryption algorithm"),
+    }
+}
+
+/// Decrypt with AES-256 in CBC mode.
+fn aes256_cbc(key: &[u8], iv: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>> {
+    cbc::Decryptor::<Aes256>::new_from_slices(key, iv)
+        .map_err(|_| anyhow!("Invalid AES key or IV"))?
+        .decrypt_padded_vec_mut::<Pkcs7>(ciphertext)
+        .map_err(|_| anyhow!("Decryption failed"))
+}
+
+/// Decrypt with triple DES in CBC mode.
+fn des3_cbc(key: &[u8], iv: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>> {
+    // The key can be longer than triple DES needs
+    let key = key
+        .get(..24)
+        .ok_or_else(|| anyhow!("Invalid triple DES key"))?;
+
+    cbc::Decryptor::<TdesEde3>::new_from_slices(key, iv)
+        .map_err(|_| anyhow!("Invalid triple DES key or IV"))?
+        .decrypt_padded_vec_mut::<Pkcs7>(ciphertext)
+        .map_err(|_| anyhow!("Decryption failed"))
+}
+
+/// A DER encoded value, only what's needed for the NSS structures.
+#[derive(Debug, Clone, Copy)]
+struct Tlv<'a> {
+    /// The type of the value.
+    tag: u8,
+    /// The encoded contents.
+    value: &'a [u8],
+}
+
+impl<'a> Tlv<'a> {
+    /// Tag of a sequence.
+    const SEQUENCE: u8 = 0x30;
+    /// Tag of an octet string.
+    const OCTET_STRING: u8 = 0x04;
+    /// Tag of an integer.
+    const INTEGER: u8 = 0x02;
+    /// Tag of an object identifier.
+    const OID: u8 = 0x06;
+
+    /// Read a value, returns the bytes after it.
+    fn read(bytes: &'a [u8]) -> Result<(Self, &'a [u8])> {
+        let (&tag, rest) = bytes
+            .split_first()
+            .ok_or_else(|| anyhow!("Unexpected end of DER data"))?;
+        let (&first, mut rest) = rest
+            .split_first()
+            .ok_or_else(|| anyhow!("Unexpected end of DER data"))?;
+
+        // Long lengths start with the number of bytes of the length
+        let length = if first & 0x80 == 0 {
+            first as usize
+        } else {
+            let size = (first & 0x7f) as usize;
+            ensure!(size <= 4 && rest.len() >= size, "Invalid DER length");
+            let (length, after) = rest.split_at(size);
+            rest = after;
+
+            length
+                .iter()
+                .fold(0, |length, byte| (length << 8) | *byte as usize)
+        };
+        ensure!(rest.len() >= length, "Unexpected end of DER data");
+
+        let (value, rest) = rest.split_at(length);
+
+        Ok((Self { tag, value }, rest))
+    }
+
+    /// Read a value that spans all bytes.
+    fn read_all(bytes: &'a [u8]) -> Result<Self> {
+        let (tlv, rest) = Self::read(bytes)?;
+        ensure!(rest.is_empty(), "Trailing DER data");
+
+        Ok(tlv)
+    }
+
+    /// The values in a sequence.
+    fn sequence(&self) -> Result<Vec<Tlv<'a>>> {
+        ensure!(self.tag == Self::SEQUENCE, "Expected a DER sequence");
+
+        let mut values = Vec::new();
+        let mut rest = self.value;
+        while !rest.is_empty() {
+            let (value, after) = Self::read(rest)?;
+            values.push(value);
+            rest = after;
+        }
+
+        Ok(values)
+    }
+
+    /// The bytes of an octet string.
+    fn octets(&self) -> Result<&'a [u8]> {
+        ensure!(
+            self.tag == Self::OCTET_STRING,
+            "Expected a DER octet string"
+        );
+
+        Ok(self.value)
+    }
+
+    /// The encoded object identifier.
+    fn oid(&self) -> Result<&'a [u8]> {
+        ensure!(self.tag == Self::OID, "Expected a DER object identifier");
+
+        Ok(self.value)
+    }
+
+    /// A small positive integer.
+    fn integer(&self) -> Result<u32> {
+        ensure!(self.tag == Self::INTEGER, "Expected a DER integer");
+        ensure!(self.value.len() <= 5, "DER integer is too large");
+
+        u32::try_from(
+            self.value
+                .iter()
+                .fold(0u64, |integer, byte| (integer << 8) | *byte as u64),
+        )
+        .map_err(|_| anyhow!("DER integer is too large"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::import::Record;
+    use anyhow::Result;
+    use std::path::Path;
+
+    /// Directory with the sample profiles.
+    const DATA_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data");
+
+    fn records(profile: &str, key: &[u8]) -> Result<Vec<Record>> {
+        super::read(&Path::new(DATA_DIR).join(profile), key)?
+            .into_iter()
+            .map(|(_, record)| record)
+            .collect()
+    }
+
+    #[test]
+    fn profile() -> Result<()> {
+        let profile = Path::new(DATA_DIR).join("firefox");
+        let key = super::key(&profile, "")?.expect("No primary password is set");
+
+        let expected = [
+            Record {
+                name: "accounts.example.com".to_string(),
+                password: "hunter2".to_string(),
+                email: Some("me@home.com".to_string()),
+                website: Some("https://accounts.example.com".to_string()),
+                skipped: Vec::new(),
+            },
+            Record {
+                name: "github.com".to_string(),
+                password: "s3cr3t".to_string(),
+                email: None,
+                website: Some("https://github.com".to_string()),
+                skipped: Vec::new(),
+            },
+        ];
+        assert_eq!(records("firefox", &key)?, expected);
+
+        // Newer profiles encrypt the logins with AES-256
+        let profile = Path::new(DATA_DIR).join("firefox-aes");
+        let key = super::key(&profile, "")?.expect("No primary password is set");
+        assert_eq!(records("firefox-aes", &key)?, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn primary_password() -> Result<()> {
+        let profile = Path::new(DATA_DIR).join("firefox-primary");
+        assert_eq!(super::key(&profile, "")?, None);
+        assert_eq!(super::key(&profile, "wrong")?, None);
+
+        let key = super::key(&profile, "keybear")?.expect("Primary password is correct");
+        assert_eq!(records("firefox-primary", &key)?[1].password, "s3cr3t");
+
+        // Older profiles use triple DES everywhere
+        let profile = Path::new(DATA_DIR).join("firefox-legacy");
+        assert_eq!(super::key(&profile, "")?, None);
+        assert_eq!(super::key(&profile, "wrong")?, None);
+
+        let key = super::key(&profile, "keybear")?.expect("Primary password is correct");
+        assert_eq!(records("firefox-legacy", &key)?[1].password, "s3cr3t");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,182 @@
+use crate::import::{email_from_username, host, Record};
+use anyhow::{anyhow, bail, Result};
+use keepass::{
+    db::{Entry, Group, Node},
+    Database, DatabaseKey,
+};
+use std::{fs::File, path::Path};
+
+/// Fields of an entry that are imported, all others are skipped.
+const IMPORTED_FIELDS: [&str; 4] = ["Title", "UserName", "Password", "URL"];
+
+/// Open a KeePass database with a master password, a key file or both.
+pub fn open(path: &Path, password: Option<&str>, key_file: Option<&Path>) -> Result<Database> {
+    let mut key = DatabaseKey::new();
+    if let Some(password) = password {
+        key = key.with_password(password);
+    }
+    if let Some(key_file) = key_file {
+        key = key.with_keyfile(
+            &mut File::open(key_file)
+                .map_err(|err| anyhow!("Could not open key file {:?}: {}", key_file, err))?,
+        )?;
+    }
+    if password.is_none() && key_file.is_none() {
+        bail!("A master password or a key file is needed to open the database");
+    }
+
+    let mut file = File::open(path).map_err(|err| anyhow!("Could not open {:?}: {}", path, err))?;
+
+    Database::open(&mut file, key)
+        .map_err(|err| anyhow!("Could not open KeePass database {:?}: {}", path, err))
+}
+
+/// All entries of the database with their path, entries in the recycle bin are left out.
+///
+/// The name of an entry is the path of its groups and its title separated by `/`, the root group
+/// isn't part of it.
+pub fn records(db: &Database) -> Vec<(String, Result<Record>)> {
+    let mut records = Vec::new();
+    walk(db, &db.root, "", &mut records);
+
+    records
+}
+
+/// Recursively add the entries in the group.
+fn walk(db: &Database, group: &Group, folder: &str, records: &mut Vec<(String, Result<Record>)>) {
+    for node in &group.children {
+        match node {
+            Node::Group(child) => {
+                if db.meta.recyclebin_uuid == Some(child.uuid) {
+                    continue;
+                }
+
+                walk(db, child, &format!("{}{}/", folder, child.name), records);
+            }
+            Node::Entry(entry) => {
+                let title = entry.get_title().unwrap_or_default().trim();
+                let source = format!("{}{}", folder, title);
+
+                records.push((source, record(entry, folder)));
+            }
+        }
+    }
+}
+
+/// Turn an entry into a password.
+fn record(entry: &Entry, folder: &str) -> Result<Record> {
+    let field = |value: Option<&str>| {
+        value
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(String::from)
+    };
+
+    let website = field(entry.get_url());
+    let name = match field(entry.get_title()) {
+        Some(title) => title,
+        None => website
+            .as_deref()
+            .and_then(host)
+            .ok_or_else(|| anyhow!("Entry has no title and no URL"))?,
+    };
+
+    // Notes, custom fields and attachments can't be stored
+    let mut skipped = entry
+        .fields
+        .iter()
+        .filter(|(key, value)| !IMPORTED_FIELDS.contains(&key.as_str()) && !value.is_empty())
+        .map(|(key, _)| key.clone())
+        .collect::<Vec<_>>();
+
+    // Only usernames that are e-mail addresses can be stored
+    let username = field(entry.get_username());
+    let email = username.as_deref().and_then(email_from_username);
+    if username.is_some() && email.is_none() {
+        skipped.push("UserName".to_string());
+    }
+    skipped.sort();
+
+    Ok(Record {
+        name: format!("{}{}", folder, name),
+        password: entry.get_password().unwrap_or_default().to_string(),
+        email,
+        website,
+        skipped,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::import::Record;
+    use anyhow::Result;
+    use std::path::Path;
+
+    /// Directory with the sample databases, their master password is `keybear`.
+    const DATA_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data");
+
+    fn records(file: &str, key_file: Option<&str>) -> Result<Vec<Record>> {
+        let key_file = key_file.map(|key_file| Path::new(DATA_DIR).join(key_file));
+        let db = super::open(
+            &Path::new(DATA_DIR).join(file),
+            Some("keybear"),
+            key_file.as_deref(),
+        )?;
+
+        let mut records = super::records(&db)
+            .into_iter()
+            .map(|(_, record)| record)
+            .collect::<Result<Vec<_>>>()?;
+        records.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(records)
+    }
+
+    #[test]
+    fn kdbx3() -> Result<()> {
+        let records = records("sample-kdbx3.kdbx", None)?;
+        assert_eq!(
+            records
+                .iter()
+                .map(|record| &record.name)
+                .collect::<Vec<_>>(),
+            ["Work/AWS/prod-root", "Work/github", "mail"]
+        );
+        assert_eq!(
+            records[2],
+            Record {
+                name: "mail".to_string(),
+                password: "hunter2".to_string(),
+                email: Some("me@home.com".to_string()),
+                website: Some("https://mail.home.com".to_string()),
+                skipped: Vec::new(),
+            }
+        );
+
+        // Usernames that aren't e-mail addresses are skipped
+        assert_eq!(records[1].email, None);
+        assert!(records[1].skipped.contains(&"UserName".to_string()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn kdbx4() -> Result<()> {
+        let records = records("sample-kdbx4.kdbx", Some("sample.key"))?;
+        assert_eq!(
+            records[0],
+            Record {
+                name: "Work/AWS/prod-root".to_string(),
+                password: "correct horse battery staple".to_string(),
+                email: Some("root@work.com".to_string()),
+                website: Some("https://aws.amazon.com".to_string()),
+                skipped: vec!["Account ID".to_string(), "Notes".to_string()],
+            }
+        );
+
+        // The key file is needed as well
+        assert!(self::records("sample-kdbx4.kdbx", None).is_err());
+
+        Ok(())
+    }
+}
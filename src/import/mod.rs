@@ -0,0 +1,95 @@
+pub mod csv;
+#[cfg(feature = "firefox")]
+pub mod firefox;
+pub mod kdbx;
+pub mod pass;
+
+use crate::{validate, vault::Metadata};
+use anyhow::{bail, Error, Result};
+use reqwest::Url;
+use std::str::FromStr;
+
+/// A password read from another password manager.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    /// Name of the password.
+    pub name: String,
+    /// The actual password.
+    pub password: String,
+    /// Associated e-mail.
+    pub email: Option<String>,
+    /// Associated website.
+    pub website: Option<String>,
+    /// Fields of the source that can't be stored, like notes.
+    pub skipped: Vec<String>,
+}
+
+impl Record {
+    /// Check whether the record can be registered.
+    pub fn validate(&self) -> Result<()> {
+        if self.name.trim().is_empty() {
+            bail!("Name is empty");
+        }
+        if self.password.is_empty() {
+            bail!("Password is empty");
+        }
+        if let Some(email) = &self.email {
+            validate::email(email)?;
+        }
+        if let Some(website) = &self.website {
+            validate::website(website)?;
+        }
+
+        Ok(())
+    }
+
+    /// The e-mail and website of the record.
+    pub fn metadata(&self) -> Metadata {
+        Metadata {
+            email: self.email.clone(),
+            website: self.website.clone(),
+        }
+    }
+}
+
+/// The host of a website, which can be without a scheme.
+pub fn host(website: &str) -> Option<String> {
+    let url = if website.contains("://") {
+        Url::parse(website)
+    } else {
+        Url::parse(&format!("https://{}", website))
+    };
+
+    url.ok()?.host_str().map(String::from)
+}
+
+/// The username as e-mail, usernames can't be stored so only e-mail addresses are kept.
+pub fn email_from_username(username: &str) -> Option<String> {
+    let username = username.trim();
+
+    username.contains('@').then(|| username.to_string())
+}
+
+/// What happens when an imported password has the name of an existing one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Conflict {
+    /// Keep the existing password and don't import the new one.
+    Skip,
+    /// Replace the existing password.
+    Overwrite,
+    /// Import the password with a number appended to its name.
+    Rename,
+}
+
+impl FromStr for Conflict {
+    type Err = Error;
+
+    fn from_str(conflict: &str) -> Result<Self> {
+        match conflict {
+            "skip" => Ok(Conflict::Skip),
+            "overwrite" => Ok(Conflict::Overwrite),
+            "rename" => Ok(Conflict::Rename),
+            other => bail!("Unknown conflict resolution \"{}\"", other),
+        }
+    }
+}
@@ -0,0 +1,206 @@
+use crate::import::{email_from_username, Record};
+use anyhow::{anyhow, bail, Result};
+use directories_next::BaseDirs;
+use log::debug;
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// Environment variable `pass` uses for the location of the store.
+const STORE_ENV_NAME: &str = "PASSWORD_STORE_DIR";
+/// Directory of the store in the home directory.
+const DEFAULT_STORE_DIRNAME: &str = ".password-store";
+/// Extension of the encrypted files.
+const EXTENSION: &str = "gpg";
+
+/// The location of the password store, like `pass` finds it.
+pub fn default_dir() -> Result<PathBuf> {
+    match env::var_os(STORE_ENV_NAME) {
+        Some(dir) => Ok(PathBuf::from(dir)),
+        None => Ok(BaseDirs::new()
+            .ok_or_else(|| anyhow!("No valid home directory found"))?
+            .home_dir()
+            .join(DEFAULT_STORE_DIRNAME)),
+    }
+}
+
+/// Read and decrypt all passwords in the store.
+///
+/// Every password is returned with its name, a password that can't be read is an error for
+/// that password only.
+pub fn read(dir: &Path) -> Result<Vec<(String, Result<Record>)>> {
+    Ok(files(dir)?
+        .into_iter()
+        .map(|(name, path)| {
+            let record = decrypt(&path).and_then(|contents| parse(&name, &contents));
+
+            (name, record)
+        })
+        .collect())
+}
+
+/// Find all encrypted files in the store sorted by name, the name is the path without the
+/// extension.
+pub fn files(dir: &Path) -> Result<Vec<(String, PathBuf)>> {
+    if !dir.is_dir() {
+        bail!("Password store {:?} is not a directory", dir);
+    }
+
+    let mut files = Vec::new();
+    walk(dir, dir, &mut files)?;
+    files.sort();
+
+    Ok(files)
+}
+
+/// Recursively add the encrypted files in the directory.
+fn walk(root: &Path, dir: &Path, files: &mut Vec<(String, PathBuf)>) -> Result<()> {
+    for entry in fs::read_dir(dir).map_err(|err| anyhow!("Could not read {:?}: {}", dir, err))? {
+        let path = entry?.path();
+
+        // Skip the git repository and other hidden files
+        if path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.starts_with('.'))
+        {
+            continue;
+        }
+
+        if path.is_dir() {
+            walk(root, &path, files)?;
+        } else if path.extension().is_some_and(|ext| ext == EXTENSION) {
+            let name = path
+                .strip_prefix(root)?
+                .with_extension("")
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+
+            files.push((name, path));
+        }
+    }
+
+    Ok(())
+}
+
+/// Decrypt a file with `gpg` the same way `pass` does.
+fn decrypt(path: &Path) -> Result<String> {
+    debug!("Decrypting {:?}", path);
+
+    let output = Command::new("gpg")
+        .args([
+            "--decrypt",
+            "--quiet",
+            "--yes",
+            "--compress-algo=none",
+            "--no-encrypt-to",
+            "--batch",
+            "--use-agent",
+        ])
+        .arg(path)
+        .output()
+        .map_err(|err| anyhow!("Could not run gpg: {}", err))?;
+
+    if !output.status.success() {
+        bail!(
+            "gpg failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    String::from_utf8(output.stdout).map_err(|_| anyhow!("Decrypted file is not valid UTF-8"))
+}
+
+/// Turn the decrypted contents into a password.
+///
+/// The first line is the password, `email:`, `login:`, `user:` or `username:` lines that look
+/// like an e-mail address and `url:` or `website:` lines are used as metadata.
+pub fn parse(name: &str, contents: &str) -> Result<Record> {
+    let mut lines = contents.lines();
+
+    let password = lines.next().unwrap_or_default().to_string();
+    if password.is_empty() {
+        bail!("First line is empty");
+    }
+
+    let mut email = None;
+    let mut website = None;
+    for (key, value) in lines.filter_map(|line| line.split_once(':')) {
+        let value = value.trim();
+        if value.is_empty() {
+            continue;
+        }
+
+        match key.trim().to_lowercase().as_str() {
+            "email" | "e-mail" | "mail" => email = Some(value.to_string()),
+            "login" | "user" | "username" if email.is_none() => email = email_from_username(value),
+            "url" | "website" => website = Some(value.to_string()),
+            _ => (),
+        }
+    }
+
+    Ok(Record {
+        name: name.to_string(),
+        password,
+        email,
+        website,
+        skipped: Vec::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::import::Record;
+    use anyhow::Result;
+    use std::fs;
+
+    #[test]
+    fn parse() -> Result<()> {
+        assert_eq!(
+            super::parse(
+                "work/mail",
+                "hunter2\nlogin: me@work.com\nurl: https://mail.work.com\nnotes: ignored\n"
+            )?,
+            Record {
+                name: "work/mail".to_string(),
+                password: "hunter2".to_string(),
+                email: Some("me@work.com".to_string()),
+                website: Some("https://mail.work.com".to_string()),
+                skipped: Vec::new(),
+            }
+        );
+
+        // Usernames aren't e-mail addresses
+        let record = super::parse("github", "hunter2\nusername: me\nEmail: me@home.com")?;
+        assert_eq!(record.email, Some("me@home.com".to_string()));
+        let record = super::parse("github", "hunter2\nusername: me")?;
+        assert_eq!(record.email, None);
+
+        assert!(super::parse("empty", "\nlogin: me@work.com").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn files() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::create_dir_all(dir.path().join("work/aws"))?;
+        fs::create_dir_all(dir.path().join(".git"))?;
+        fs::write(dir.path().join("work/aws/prod.gpg"), "")?;
+        fs::write(dir.path().join("mail.gpg"), "")?;
+        fs::write(dir.path().join(".gpg-id"), "")?;
+        fs::write(dir.path().join(".git/config.gpg"), "")?;
+
+        let names = super::files(dir.path())?
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["mail", "work/aws/prod"]);
+
+        Ok(())
+    }
+}
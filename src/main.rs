@@ -1,14 +1,39 @@
 #![forbid(unsafe_code)]
 
+mod audit;
+mod backup;
+mod clipboard;
 mod command;
 mod config;
+mod export;
+mod file;
+mod generator;
+mod import;
 mod net;
+mod otp;
+mod output;
+mod prompt;
+mod strength;
+mod trash;
+mod tree;
+mod validate;
+mod vault;
 
-use crate::config::Config;
-use anyhow::{anyhow, bail, Result};
-use clap::clap_app;
+use crate::{
+    config::Config,
+    export::{kdbx::Cipher, Plaintext},
+    generator::{Append, Capitalization, CharacterClass, Characters, Generator, Passphrase},
+    import::{
+        csv::{Mapping, Preset},
+        Conflict,
+    },
+    output::Format,
+    vault::{Metadata, Selection},
+};
+use anyhow::{anyhow, bail, ensure, Result};
+use clap::{clap_app, ArgMatches};
 use directories_next::ProjectDirs;
-use std::path::PathBuf;
+use std::{path::PathBuf, process};
 
 /// Names used for the directory in the configuration folder.
 pub const PROJECT_NAME: (&str, &str, &str) = ("com", "keybear", "keybear");
@@ -59,6 +84,12 @@ async fn main() -> Result<()> {
             global(true)
             multiple_occurrences(true)
             "Verbose mode")
+        // The global output format argument
+        (@arg format: -F --format
+            +takes_value
+            possible_values(&["plain", "json", "tsv"])
+            global(true)
+            "Output format, errors are also reported as JSON with \"json\" [default: plain]")
 
         (@subcommand register =>
             (about: "Register this client to the server")
@@ -68,36 +99,244 @@ async fn main() -> Result<()> {
             (about: "Show an existing password")
             (@setting DisableVersion)
             (@arg NAME: +required "Name of the password")
+            (@arg id: --id +takes_value "ID of the password, needed when several have the same name")
+            (@arg field: -f --field +takes_value
+                possible_values(&["password", "email", "website", "id"])
+                "Only show this field")
+            (@arg all: -a --all
+                conflicts_with_all(&["field", "clip"])
+                "Show all fields of the password")
+            (@arg clip: -C --clip "Copy the password or field to the clipboard instead of showing it")
         )
         (@subcommand ls =>
             (alias: "list")
-            (about: "List all passwords")
+            (about: "List all passwords as a tree")
+            (@setting DisableVersion)
+            (@arg FOLDER: "Only list the passwords in this folder")
+            (@arg flat: --flat "List one name per line instead of a tree")
+        )
+        (@subcommand find =>
+            (alias: "search")
+            (about: "Find passwords by name, e-mail or website")
             (@setting DisableVersion)
+            (@arg QUERY: +required "Text to search for, case-insensitive")
+            (@arg regex: -r --regex conflicts_with("fuzzy") "Match the query as a regular expression")
+            (@arg fuzzy: -z --fuzzy "Match the query fuzzily, ranked by how well it matches")
         )
         (@subcommand generate =>
             (about: "Generate a new pass-phrase")
             (@setting DisableVersion)
             (@arg NAME: +required "Name of the password")
-            (@arg length: -l --length default_value("5") "Amount of words to generate")
+            (@arg length: -l --length +takes_value
+                "Amount of words to generate, or characters with --characters [default: 5 words, 24 characters]")
+            (@arg separator: --separator +takes_value
+                conflicts_with("characters")
+                "Text between the words [default: space]")
+            (@arg capitalize: --capitalize +takes_value
+                possible_values(&["none", "first", "upper", "random"])
+                conflicts_with("characters")
+                "How the words are capitalized [default: random]")
+            (@arg append: --append +takes_value +use_delimiter
+                possible_values(&["number", "symbol"])
+                conflicts_with("characters")
+                "Comma separated random characters to append to the words")
+            (@arg wordlist: --wordlist +takes_value
+                conflicts_with("characters")
+                "File with a word on every line to pick from [default: EFF long list]")
+            (@arg characters: --characters "Generate random characters instead of words")
+            (@arg classes: --classes +takes_value +use_delimiter
+                possible_values(&["lower", "upper", "digits", "symbols"])
+                requires("characters")
+                "Comma separated character classes to use [default: all]")
+            (@arg min_per_class: --("min-per-class") +takes_value
+                requires("characters")
+                "Minimum amount of characters from every class [default: 1]")
+            (@arg no_look_alikes: --("no-look-alikes")
+                requires("characters")
+                "Exclude characters that look alike, like 'l', '1' and 'I'")
+            (@arg alphabet: --alphabet +takes_value
+                requires("characters")
+                conflicts_with("classes")
+                "Custom characters to pick from instead of the classes")
+            (@arg email: --email +takes_value {validate::email} "Associated e-mail address")
+            (@arg website: --website +takes_value {validate::website} "Associated website")
+            (@arg in_place: -i --("in-place")
+                "Replace the password of the existing entry instead of adding a new one")
+            (@arg id: --id +takes_value requires("in_place") "ID of the password to replace, needed when several have the same name")
             (@arg echo: -e --echo "Echo the password back to the console")
+            (@arg clip: -C --clip "Copy the password to the clipboard")
         )
         (@subcommand insert =>
             (alias: "new")
             (about: "Insert a new password")
             (@setting DisableVersion)
             (@arg NAME: +required "Name of the password")
-            (@arg PASSWORD: +required "Actual password")
+            (@arg PASSWORD: "Actual password, asked for or read from stdin when omitted")
+            (@arg multiline: -m --multiline
+                conflicts_with("PASSWORD")
+                "Read a multi-line password from stdin until EOF")
+            (@arg email: --email +takes_value {validate::email} "Associated e-mail address")
+            (@arg website: --website +takes_value {validate::website} "Associated website")
             (@arg echo: -e --echo "Echo the password back to the console")
         )
         (@subcommand edit =>
             (about: "Edit an existing password using a text editor")
             (@setting DisableVersion)
             (@arg NAME: +required "Name of the password")
+            (@arg id: --id +takes_value "ID of the password, needed when several have the same name")
         )
         (@subcommand rm =>
             (about: "Remove an existing password")
             (@setting DisableVersion)
-            (@arg NAME: +required "Name of the password")
+            (@arg NAME: +required "Name or glob pattern of the passwords, or folder with --recursive")
+            (@arg id: --id +takes_value conflicts_with("recursive") "ID of the password, needed when several have the same name")
+            (@arg recursive: -r --recursive "Remove all passwords in the folder")
+            (@arg force: -f --force "Don't ask for confirmation, required when not on a terminal and to remove passwords that can't be kept in the trash")
+            (@arg dry_run: -n --("dry-run") "Only list the passwords that would be removed")
+        )
+        (@subcommand import =>
+            (about: "Import passwords from another password manager")
+            (@setting DisableVersion)
+            (@setting SubcommandRequiredElseHelp)
+            (@subcommand pass =>
+                (about: "Import from a pass password store, decrypting with gpg")
+                (@setting DisableVersion)
+                (@arg DIR: "Directory of the password store [default: $PASSWORD_STORE_DIR or ~/.password-store]")
+                (@arg dry_run: -n --("dry-run") "Only report what would be imported")
+                (@arg on_conflict: --("on-conflict") +takes_value
+                    possible_values(&["skip", "overwrite", "rename"])
+                    "What to do when a password with the name already exists [default: skip]")
+            )
+            (@subcommand csv =>
+                (about: "Import from a CSV file, like an export of Bitwarden, Chrome, Firefox or LastPass")
+                (@setting DisableVersion)
+                (@arg FILE: +required "CSV file with a header row")
+                (@arg preset: -p --preset +takes_value
+                    possible_values(&["bitwarden", "chrome", "chromium", "firefox", "lastpass"])
+                    conflicts_with("map")
+                    "Password manager that exported the file [default: detected from the columns]")
+                (@arg map: -m --map +takes_value
+                    "Columns of the fields, like name=title,password=pass,website=url,email=username")
+                (@arg report: --report +takes_value
+                    "File the rows that fail are written to with their plaintext passwords [default: FILE-failed.csv]")
+                (@arg dry_run: -n --("dry-run") "Only report what would be imported")
+                (@arg on_conflict: --("on-conflict") +takes_value
+                    possible_values(&["skip", "overwrite", "rename"])
+                    "What to do when a password with the name already exists [default: skip]")
+            )
+            (@subcommand firefox =>
+                (about: "Import the saved logins of a Firefox profile")
+                (@setting DisableVersion)
+                (@arg PROFILE_DIR: +required "Profile directory containing logins.json and key4.db")
+                (@arg dry_run: -n --("dry-run") "Only report what would be imported")
+                (@arg on_conflict: --("on-conflict") +takes_value
+                    possible_values(&["skip", "overwrite", "rename"])
+                    "What to do when a password with the name already exists [default: skip]")
+            )
+            (@subcommand kdbx =>
+                (alias: "keepass")
+                (about: "Import from a KeePass 2 database, KDBX 3.1 or 4")
+                (@setting DisableVersion)
+                (@arg FILE: +required "KeePass database file")
+                (@arg key_file: -k --("key-file") +takes_value "Key file of the database")
+                (@arg no_password: --("no-password") requires("key_file")
+                    "Don't ask for a master password, the database is only protected by the key file")
+                (@arg dry_run: -n --("dry-run") "Only report what would be imported")
+                (@arg on_conflict: --("on-conflict") +takes_value
+                    possible_values(&["skip", "overwrite", "rename"])
+                    "What to do when a password with the name already exists [default: skip]")
+            )
+        )
+        (@subcommand export =>
+            (about: "Write the passwords unencrypted to a file or stdout, or to a KeePass database")
+            (@setting DisableVersion)
+            (@arg PREFIX: "Only export the passwords in this folder")
+            (@arg export_format: -t --("as") +takes_value +required
+                possible_values(&["json", "csv", "dotenv", "kdbx"])
+                "Format of the exported passwords, dotenv only contains the passwords and kdbx is a new KDBX 4 database protected by a master password")
+            (@arg output: -o --output +takes_value required_if_eq("export_format", "kdbx")
+                "File to write to, created only readable by you [default: stdout]")
+            (@arg plaintext: --("i-know-this-is-plaintext")
+                "Also write to a terminal or to a file everyone can read")
+            (@arg cipher: --cipher +takes_value possible_values(&["aes", "chacha20"])
+                "Cipher a KeePass database is encrypted with [default: aes]")
+        )
+        (@subcommand otp =>
+            (about: "Show a two-factor authentication code of a stored secret")
+            (@setting DisableVersion)
+            (@setting SubcommandsNegateReqs)
+            (@arg NAME: +required "Name of the secret")
+            (@arg id: --id +takes_value "ID of the secret, needed when several have the same name")
+            (@arg clip: -C --clip "Copy the code to the clipboard")
+            (@subcommand insert =>
+                (about: "Store a secret for two-factor authentication codes")
+                (@setting DisableVersion)
+                (@arg NAME: +required "Name of the secret")
+                (@arg SECRET: "otpauth:// URI or base32 secret, read from the terminal or stdin when not given")
+            )
+        )
+        (@subcommand trash =>
+            (about: "Manage the passwords removed with rm")
+            (@setting DisableVersion)
+            (@setting SubcommandRequiredElseHelp)
+            (@subcommand ls =>
+                (alias: "list")
+                (about: "List the removed passwords")
+                (@setting DisableVersion)
+            )
+            (@subcommand purge =>
+                (about: "Permanently delete all removed passwords")
+                (@setting DisableVersion)
+                (@arg force: -f --force "Don't ask for confirmation, required when not on a terminal")
+            )
+        )
+        (@subcommand restore =>
+            (about: "Restore a removed password from the trash")
+            (@setting DisableVersion)
+            (@arg NAME: +required "Name of the removed password")
+            (@arg id: --id +takes_value "ID the password had, when several with the name were removed")
+        )
+        (@subcommand mv =>
+            (alias: "rename")
+            (about: "Rename an existing password")
+            (@setting DisableVersion)
+            (@arg OLD: +required "Current name of the password")
+            (@arg NEW: +required "New name of the password")
+            (@arg id: --id +takes_value "ID of the password to rename, needed when several have the same name")
+            (@arg force: -f --force "Replace the password with the new name if it already exists, it's kept in the trash")
+        )
+        (@subcommand cp =>
+            (alias: "copy")
+            (about: "Copy an existing password to a new name")
+            (@setting DisableVersion)
+            (@arg SOURCE: +required "Name of the password to copy")
+            (@arg DESTINATION: +required "Name of the new password")
+            (@arg id: --id +takes_value "ID of the password to copy, needed when several have the same name")
+            (@arg email: --email +takes_value {validate::email} "Associated e-mail address [default: the one of SOURCE]")
+            (@arg website: --website +takes_value {validate::website} "Associated website [default: the one of SOURCE]")
+        )
+        (@subcommand audit =>
+            (about: "Check all passwords for reuse, weak and similar passwords and missing websites")
+            (@setting DisableVersion)
+        )
+        (@subcommand backup =>
+            (about: "Write all passwords to a new file encrypted with a passphrase, read from stdin when not on a terminal")
+            (@setting DisableVersion)
+            (@arg FILE: +required "Backup file to create")
+        )
+        (@subcommand ("restore-backup") =>
+            (about: "Import all passwords from a backup")
+            (@setting DisableVersion)
+            (@arg FILE: +required "Backup file created with the backup command")
+            (@arg dry_run: -n --("dry-run") "Only check the backup and report what would be imported")
+            (@arg on_conflict: --("on-conflict") +takes_value
+                possible_values(&["skip", "overwrite", "rename"])
+                "What to do when a password with the name already exists [default: skip]")
+        )
+        (@subcommand (clipboard::CLEAR_SUBCOMMAND) =>
+            (about: "Clear the clipboard after a timeout, used internally")
+            (@setting DisableVersion)
+            (@setting Hidden)
         )
     )
     .get_matches();
@@ -109,11 +348,33 @@ async fn main() -> Result<()> {
         .quiet(matches.is_present("quiet"))
         .init()?;
 
+    // Get the output format argument, it overwrites the one in the configuration file
+    let format = if matches.is_present("format") {
+        Some(matches.value_of_t_or_exit::<Format>("format"))
+    } else {
+        None
+    };
+
     // Get the configuration argument
     let config_path: PathBuf = matches.value_of_t_or_exit("config");
     // Load the configuration file
-    let config = Config::from_file(&config_path)?;
+    let mut config = match Config::from_file(&config_path) {
+        Ok(config) => config,
+        Err(err) => return report(err, format.unwrap_or_default()),
+    };
+    if let Some(format) = format {
+        config.set_format(format);
+    }
 
+    let format = config.format();
+    match run(&matches, config).await {
+        Ok(()) => Ok(()),
+        Err(err) => report(err, format),
+    }
+}
+
+/// Run the invoked subcommand.
+async fn run(matches: &ArgMatches, config: Config) -> Result<()> {
     // Use the proper subcommand module for the invoked subcommand.
     match matches
         .subcommand()
@@ -124,43 +385,328 @@ async fn main() -> Result<()> {
         // kb show
         ("show", subcommand) => {
             let name = subcommand.value_of_t_or_exit::<String>("NAME");
+            let selection = Selection::new(&name, subcommand.value_of("id"));
+            let field = if subcommand.is_present("field") {
+                Some(subcommand.value_of_t_or_exit::<command::Field>("field"))
+            } else {
+                None
+            };
+            let all = subcommand.is_present("all");
+            let clip = subcommand.is_present("clip");
 
-            command::show(config, &name).await
+            command::show(config, &selection, field, all, clip).await
         }
         // kb ls
-        ("ls", _) => command::ls(config).await,
+        ("ls", subcommand) => {
+            let folder = subcommand.value_of("FOLDER");
+            let flat = subcommand.is_present("flat");
+
+            command::ls(config, folder, flat).await
+        }
+        // kb find
+        ("find", subcommand) => {
+            let query = subcommand.value_of_t_or_exit::<String>("QUERY");
+            let matcher = if subcommand.is_present("regex") {
+                command::Matcher::regex(&query)?
+            } else if subcommand.is_present("fuzzy") {
+                command::Matcher::fuzzy(&query)
+            } else {
+                command::Matcher::substring(&query)
+            };
+
+            command::find(config, &query, &matcher).await
+        }
         // kb generate
         ("generate", subcommand) => {
             let name = subcommand.value_of_t_or_exit::<String>("NAME");
-            let length = subcommand.value_of_t_or_exit::<usize>("length");
+            let selection = Selection::new(&name, subcommand.value_of("id"));
+            let in_place = subcommand.is_present("in_place");
             let echo = subcommand.is_present("echo");
+            let clip = subcommand.is_present("clip");
+            let metadata = metadata(subcommand);
+            let generator = generator(&config, subcommand);
 
-            command::generate(config, &name, length, echo).await
+            command::generate(
+                config, &selection, &generator, &metadata, in_place, echo, clip,
+            )
+            .await
         }
         // kb insert
         ("insert", subcommand) => {
             let name = subcommand.value_of_t_or_exit::<String>("NAME");
-            let password = subcommand.value_of_t_or_exit::<String>("PASSWORD");
+            let password = subcommand.value_of("PASSWORD");
+            let metadata = metadata(subcommand);
+            let multiline = subcommand.is_present("multiline");
             let echo = subcommand.is_present("echo");
 
-            command::insert(config, &name, &password, echo).await
+            command::insert(config, &name, password, &metadata, multiline, echo).await
         }
         // kb edit
         ("edit", subcommand) => {
             let name = subcommand.value_of_t_or_exit::<String>("NAME");
+            let selection = Selection::new(&name, subcommand.value_of("id"));
 
-            command::edit(config, &name)
+            command::edit(config, &selection).await
         }
         // kb rm
         ("rm", subcommand) => {
             let name = subcommand.value_of_t_or_exit::<String>("NAME");
+            let selection = Selection::new(&name, subcommand.value_of("id"));
+            let recursive = subcommand.is_present("recursive");
+            let force = subcommand.is_present("force");
+            let dry_run = subcommand.is_present("dry_run");
+
+            command::rm(config, &selection, recursive, force, dry_run).await
+        }
+        // kb import
+        ("import", subcommand) => match subcommand
+            .subcommand()
+            .ok_or_else(|| anyhow!("No subcommand invoked"))?
+        {
+            ("pass", subcommand) => {
+                let dir = subcommand.value_of("DIR").map(PathBuf::from);
+                let options = import_options(subcommand);
+
+                command::import_pass(config, dir.as_deref(), options).await
+            }
+            ("csv", subcommand) => {
+                let file = subcommand.value_of_t_or_exit::<PathBuf>("FILE");
+                let mapping = if subcommand.is_present("map") {
+                    Some(subcommand.value_of_t_or_exit::<Mapping>("map"))
+                } else if subcommand.is_present("preset") {
+                    Some(Mapping::from(
+                        subcommand.value_of_t_or_exit::<Preset>("preset"),
+                    ))
+                } else {
+                    None
+                };
+                let report = subcommand.value_of("report").map(PathBuf::from);
+                let options = import_options(subcommand);
+
+                command::import_csv(config, &file, mapping, report.as_deref(), options).await
+            }
+            ("firefox", subcommand) => {
+                let profile = subcommand.value_of_t_or_exit::<PathBuf>("PROFILE_DIR");
+                let options = import_options(subcommand);
+
+                command::import_firefox(config, &profile, options).await
+            }
+            ("kdbx", subcommand) => {
+                let file = subcommand.value_of_t_or_exit::<PathBuf>("FILE");
+                let key_file = subcommand.value_of("key_file").map(PathBuf::from);
+                let options = import_options(subcommand);
+
+                command::import_kdbx(
+                    config,
+                    &file,
+                    key_file.as_deref(),
+                    !subcommand.is_present("no_password"),
+                    options,
+                )
+                .await
+            }
+            (other, _) => bail!("Unrecognized subcommand \"{}\"", other),
+        },
+        // kb trash
+        ("trash", subcommand) => match subcommand
+            .subcommand()
+            .ok_or_else(|| anyhow!("No subcommand invoked"))?
+        {
+            ("ls", _) => command::trash_ls(config),
+            ("purge", subcommand) => command::trash_purge(config, subcommand.is_present("force")),
+            (other, _) => bail!("Unrecognized subcommand \"{}\"", other),
+        },
+        // kb restore
+        ("restore", subcommand) => {
+            let name = subcommand.value_of_t_or_exit::<String>("NAME");
+            let id = subcommand.value_of("id");
+
+            command::restore(config, &name, id).await
+        }
+        // kb mv
+        ("mv", subcommand) => {
+            let old = subcommand.value_of_t_or_exit::<String>("OLD");
+            let new = subcommand.value_of_t_or_exit::<String>("NEW");
+            let selection = Selection::new(&old, subcommand.value_of("id"));
+            let force = subcommand.is_present("force");
+
+            command::mv(config, &selection, &new, force).await
+        }
+        // kb cp
+        ("cp", subcommand) => {
+            let source = subcommand.value_of_t_or_exit::<String>("SOURCE");
+            let destination = subcommand.value_of_t_or_exit::<String>("DESTINATION");
+            let selection = Selection::new(&source, subcommand.value_of("id"));
+            let metadata = metadata(subcommand);
+
+            command::cp(config, &selection, &destination, &metadata).await
+        }
+        // kb export
+        ("export", subcommand) => {
+            let output = subcommand.value_of("output").map(PathBuf::from);
+
+            match subcommand.value_of("export_format") {
+                // kb export --as kdbx
+                Some("kdbx") => {
+                    let file = output
+                        .ok_or_else(|| anyhow!("A KeePass database needs an --output file"))?;
+                    let cipher = if subcommand.is_present("cipher") {
+                        subcommand.value_of_t_or_exit::<Cipher>("cipher")
+                    } else {
+                        Cipher::Aes
+                    };
+
+                    command::export_kdbx(config, &file, subcommand.value_of("PREFIX"), cipher).await
+                }
+                _ => {
+                    ensure!(
+                        !subcommand.is_present("cipher"),
+                        "--cipher is only used with --as kdbx"
+                    );
+                    let format = subcommand.value_of_t_or_exit::<Plaintext>("export_format");
+
+                    command::export(
+                        config,
+                        format,
+                        subcommand.value_of("PREFIX"),
+                        output.as_deref(),
+                        subcommand.is_present("plaintext"),
+                    )
+                    .await
+                }
+            }
+        }
+        ("otp", subcommand) => match subcommand.subcommand() {
+            // kb otp insert
+            Some(("insert", subcommand)) => {
+                let name = subcommand.value_of_t_or_exit::<String>("NAME");
+
+                command::otp_insert(config, &name, subcommand.value_of("SECRET")).await
+            }
+            Some((other, _)) => bail!("Unrecognized subcommand \"{}\"", other),
+            // kb otp
+            None => {
+                let name = subcommand.value_of_t_or_exit::<String>("NAME");
+                let selection = Selection::new(&name, subcommand.value_of("id"));
+
+                command::otp(config, &selection, subcommand.is_present("clip")).await
+            }
+        },
+        // kb audit
+        ("audit", _) => command::audit(config).await,
+        // kb backup
+        ("backup", subcommand) => {
+            let file = subcommand.value_of_t_or_exit::<PathBuf>("FILE");
 
-            command::rm(config, &name).await
+            command::backup(config, &file).await
         }
+        // kb restore-backup
+        ("restore-backup", subcommand) => {
+            let file = subcommand.value_of_t_or_exit::<PathBuf>("FILE");
+            let options = import_options(subcommand);
+
+            command::restore_backup(config, &file, options).await
+        }
+        // Spawned by the clipboard functionality
+        (clipboard::CLEAR_SUBCOMMAND, _) => clipboard::clear(&config),
         (other, _) => bail!("Unrecognized subcommand \"{}\"", other),
-    }?;
+    }
+}
+
+/// Report an error in the output format.
+fn report(err: anyhow::Error, format: Format) -> Result<()> {
+    match format {
+        Format::Json => {
+            output::print_json_error(&err);
+
+            process::exit(1);
+        }
+        _ => Err(err),
+    }
+}
 
-    Ok(())
+/// Get the e-mail and website from the arguments.
+fn metadata(matches: &ArgMatches) -> Metadata {
+    Metadata {
+        email: matches.value_of("email").map(String::from),
+        website: matches.value_of("website").map(String::from),
+    }
+}
+
+/// Get the conflict resolution and dry run from the import arguments.
+fn import_options(matches: &ArgMatches) -> command::ImportOptions {
+    command::ImportOptions {
+        conflict: if matches.is_present("on_conflict") {
+            matches.value_of_t_or_exit::<Conflict>("on_conflict")
+        } else {
+            Conflict::Skip
+        },
+        dry_run: matches.is_present("dry_run"),
+    }
+}
+
+/// Get the password generator from the arguments, falling back to the configuration file.
+fn generator(config: &Config, matches: &ArgMatches) -> Generator {
+    let length = if matches.is_present("length") {
+        Some(matches.value_of_t_or_exit::<usize>("length"))
+    } else {
+        None
+    };
+
+    if matches.is_present("characters") {
+        Generator::Characters(Characters {
+            length: length.unwrap_or(generator::DEFAULT_CHARACTERS),
+            classes: if matches.is_present("classes") {
+                // A class given twice would count twice toward the minimum
+                let mut classes = matches.values_of_t_or_exit::<CharacterClass>("classes");
+                let mut seen = Vec::with_capacity(classes.len());
+                classes.retain(|class| {
+                    let new = !seen.contains(class);
+                    seen.push(*class);
+                    new
+                });
+                classes
+            } else {
+                CharacterClass::ALL.to_vec()
+            },
+            min_per_class: if matches.is_present("min_per_class") {
+                matches.value_of_t_or_exit::<usize>("min_per_class")
+            } else {
+                generator::DEFAULT_MIN_PER_CLASS
+            },
+            exclude_look_alikes: matches.is_present("no_look_alikes"),
+            alphabet: matches.value_of("alphabet").map(String::from),
+        })
+    } else {
+        let settings = config.generate();
+        let default = Passphrase::default();
+
+        Generator::Passphrase(Passphrase {
+            words: length.or_else(|| settings.words()).unwrap_or(default.words),
+            separator: matches
+                .value_of("separator")
+                .or_else(|| settings.separator())
+                .map(String::from)
+                .unwrap_or(default.separator),
+            capitalize: if matches.is_present("capitalize") {
+                matches.value_of_t_or_exit::<Capitalization>("capitalize")
+            } else {
+                settings.capitalize().unwrap_or(default.capitalize)
+            },
+            append: if matches.is_present("append") {
+                matches.values_of_t_or_exit::<Append>("append")
+            } else {
+                settings
+                    .append()
+                    .map(<[_]>::to_vec)
+                    .unwrap_or(default.append)
+            },
+            wordlist: matches
+                .value_of("wordlist")
+                .map(PathBuf::from)
+                .or_else(|| settings.wordlist().map(PathBuf::from)),
+        })
+    }
 }
 
 /// Get the default configuration file location.
@@ -83,7 +83,7 @@ impl<'a> Client<'a> {
         debug!("Trying to get nonce to make a request to \"{}\"", path);
 
         // Build the proxy URL for the nonce
-        let url = proxy_url(&self.config.url(), v1::NONCE)?;
+        let url = proxy_url(self.config.url(), v1::NONCE)?;
 
         // Build the request for the nonce
         let request = self
@@ -101,7 +101,7 @@ impl<'a> Client<'a> {
         ensure!(
             response.status().is_success(),
             "{}: {}",
-            response.status().to_string(),
+            response.status(),
             response.text().await?
         );
 
@@ -112,7 +112,7 @@ impl<'a> Client<'a> {
         ensure!(bytes.len() == 12, "Nonce response size is incorrect");
 
         // Construct the nonce
-        let nonce = Nonce::from_slice(&bytes);
+        let nonce = Nonce::from(bytes);
 
         debug!("Creating {} request to \"{}\"", &method, path);
 
@@ -120,7 +120,7 @@ impl<'a> Client<'a> {
         let shared_key = self.config.shared_key()?;
 
         // Build the proxy URL
-        let url = proxy_url(&self.config.url(), path)?;
+        let url = proxy_url(self.config.url(), path)?;
 
         // Build the request
         let request = self
@@ -152,7 +152,7 @@ impl<'a> Client<'a> {
         ensure!(
             response.status().is_success(),
             "{}: {}",
-            response.status().to_string(),
+            response.status(),
             response.text().await?
         );
 
@@ -190,3 +190,44 @@ pub fn proxy_url(url: &str, path: &str) -> Result<Url> {
         .join(path)
         .map_err(|err| anyhow!("could not build proxy URL: {}", err))
 }
+
+/// Append a segment to a route, percent-encoded so characters like `/`, `?` and `#` stay part of
+/// it.
+pub fn route(route: &str, segment: &str) -> Result<String> {
+    // A URL path can't contain these as a segment, not even when they're percent-encoded
+    ensure!(
+        segment != "." && segment != "..",
+        "\"{}\" can't be used in a URL",
+        segment
+    );
+
+    let mut url = Url::parse("http://localhost")?.join(route)?;
+    url.path_segments_mut()
+        .map_err(|_| anyhow!("could not build route from \"{}\"", route))?
+        .push(segment);
+
+    Ok(url.path().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    #[test]
+    fn route() -> Result<()> {
+        assert_eq!(super::route("/v1/passwords", "mail")?, "/v1/passwords/mail");
+        assert_eq!(
+            super::route("/v1/passwords", "web/a?b#c d")?,
+            "/v1/passwords/web%2Fa%3Fb%23c%20d"
+        );
+        assert!(super::route("/v1/passwords", "..").is_err());
+
+        // The segment survives joining it with the server URL
+        assert_eq!(
+            super::proxy_url("example.onion", &super::route("/v1/passwords", "../x")?)?.path(),
+            "/v1/passwords/..%2Fx"
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,347 @@
+use anyhow::{anyhow, bail, ensure, Error, Result};
+use hmac::{digest::KeyInit, Hmac, Mac};
+use sha1::Sha1;
+use sha2::{Sha256, Sha512};
+use std::{
+    convert::TryInto,
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+use url::Url;
+
+/// Characters of the base32 alphabet of RFC 4648.
+const BASE32: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// Hash function of the HMAC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Algorithm {
+    /// SHA-1, the default.
+    Sha1,
+    /// SHA-256.
+    Sha256,
+    /// SHA-512.
+    Sha512,
+}
+
+impl FromStr for Algorithm {
+    type Err = Error;
+
+    fn from_str(algorithm: &str) -> Result<Self> {
+        match algorithm.to_ascii_uppercase().as_str() {
+            "SHA1" => Ok(Algorithm::Sha1),
+            "SHA256" => Ok(Algorithm::Sha256),
+            "SHA512" => Ok(Algorithm::Sha512),
+            _ => bail!("Unknown algorithm \"{}\"", algorithm),
+        }
+    }
+}
+
+impl Display for Algorithm {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Algorithm::Sha1 => "SHA1",
+            Algorithm::Sha256 => "SHA256",
+            Algorithm::Sha512 => "SHA512",
+        })
+    }
+}
+
+/// How the moving factor of the codes is determined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    /// Time-based as in RFC 6238, a new code every period in seconds.
+    Totp {
+        /// Seconds a code is valid.
+        period: u64,
+    },
+    /// Counter-based as in RFC 4226, the counter is increased after every code.
+    Hotp {
+        /// Counter of the next code.
+        counter: u64,
+    },
+}
+
+/// A shared secret for two-factor authentication codes.
+///
+/// It's stored as an `otpauth://` URI, the format of the QR codes of authenticator apps.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Otp {
+    /// Percent-encoded label of the URI, usually the issuer and account.
+    pub label: String,
+    /// Service the secret belongs to.
+    pub issuer: Option<String>,
+    /// The decoded shared secret.
+    pub secret: Vec<u8>,
+    /// Hash function of the HMAC.
+    pub algorithm: Algorithm,
+    /// Number of digits of a code.
+    pub digits: u32,
+    /// Time or counter based.
+    pub kind: Kind,
+}
+
+impl Otp {
+    /// The code for the counter.
+    pub fn hotp(&self, counter: u64) -> String {
+        let hash = match self.algorithm {
+            Algorithm::Sha1 => hmac::<Hmac<Sha1>>(&self.secret, counter),
+            Algorithm::Sha256 => hmac::<Hmac<Sha256>>(&self.secret, counter),
+            Algorithm::Sha512 => hmac::<Hmac<Sha512>>(&self.secret, counter),
+        };
+
+        // Dynamic truncation, the last nibble is the offset of 31 bits
+        let offset = usize::from(hash[hash.len() - 1] & 0xf);
+        let binary = u32::from_be_bytes(
+            hash[offset..offset + 4]
+                .try_into()
+                .expect("Hashes are longer than 20 bytes"),
+        ) & 0x7fff_ffff;
+
+        format!(
+            "{:0digits$}",
+            u64::from(binary) % 10u64.pow(self.digits),
+            digits = self.digits as usize
+        )
+    }
+
+    /// The code at the time in seconds since the UNIX epoch and the seconds it's still valid.
+    ///
+    /// Counter-based secrets return the code of their counter, without a validity.
+    pub fn code(&self, time: u64) -> (String, Option<u64>) {
+        match self.kind {
+            Kind::Totp { period } => (self.hotp(time / period), Some(period - time % period)),
+            Kind::Hotp { counter } => (self.hotp(counter), None),
+        }
+    }
+}
+
+impl FromStr for Otp {
+    type Err = Error;
+
+    /// Parse an `otpauth://` URI or a bare base32 secret, which is a TOTP secret with the
+    /// defaults of authenticator apps.
+    fn from_str(value: &str) -> Result<Self> {
+        let value = value.trim();
+        if !value.starts_with("otpauth:") {
+            return Ok(Self {
+                label: String::new(),
+                issuer: None,
+                secret: base32_decode(value)?,
+                algorithm: Algorithm::Sha1,
+                digits: 6,
+                kind: Kind::Totp { period: 30 },
+            });
+        }
+
+        let url = Url::parse(value).map_err(|err| anyhow!("Invalid otpauth URI: {}", err))?;
+        let query = |key: &str| {
+            url.query_pairs()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value.into_owned())
+        };
+        let number = |key: &str| {
+            query(key)
+                .map(|value| {
+                    value
+                        .parse::<u64>()
+                        .map_err(|_| anyhow!("Invalid {} \"{}\" in otpauth URI", key, value))
+                })
+                .transpose()
+        };
+
+        let kind = match url.host_str() {
+            Some("totp") => Kind::Totp {
+                period: number("period")?.unwrap_or(30),
+            },
+            Some("hotp") => Kind::Hotp {
+                counter: number("counter")?.ok_or_else(|| anyhow!("HOTP URI without a counter"))?,
+            },
+            _ => bail!("The otpauth URI type must be \"totp\" or \"hotp\""),
+        };
+        ensure!(
+            kind != Kind::Totp { period: 0 },
+            "The period must be at least one second"
+        );
+
+        let digits = number("digits")?.unwrap_or(6);
+        ensure!(
+            (6..=10).contains(&digits),
+            "A code has between 6 and 10 digits, not {}",
+            digits
+        );
+
+        Ok(Self {
+            label: url.path().trim_start_matches('/').to_string(),
+            issuer: query("issuer"),
+            secret: base32_decode(
+                &query("secret").ok_or_else(|| anyhow!("The otpauth URI has no secret"))?,
+            )?,
+            algorithm: query("algorithm")
+                .map(|algorithm| algorithm.parse())
+                .transpose()?
+                .unwrap_or(Algorithm::Sha1),
+            digits: digits as u32,
+            kind,
+        })
+    }
+}
+
+impl Display for Otp {
+    /// Format it as an `otpauth://` URI with every parameter.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let (kind, key, value) = match self.kind {
+            Kind::Totp { period } => ("totp", "period", period),
+            Kind::Hotp { counter } => ("hotp", "counter", counter),
+        };
+
+        let mut url = Url::parse(&format!("otpauth://{}", kind)).map_err(|_| fmt::Error)?;
+        url.set_path(&format!("/{}", self.label));
+        {
+            let mut query = url.query_pairs_mut();
+            query.append_pair("secret", &base32_encode(&self.secret));
+            if let Some(issuer) = &self.issuer {
+                query.append_pair("issuer", issuer);
+            }
+            query
+                .append_pair("algorithm", &self.algorithm.to_string())
+                .append_pair("digits", &self.digits.to_string())
+                .append_pair(key, &value.to_string());
+        }
+
+        f.write_str(url.as_str())
+    }
+}
+
+/// The HMAC of the counter as big-endian bytes.
+fn hmac<M>(key: &[u8], counter: u64) -> Vec<u8>
+where
+    M: Mac + KeyInit,
+{
+    let mut mac = <M as Mac>::new_from_slice(key).expect("HMAC takes keys of any size");
+    mac.update(&counter.to_be_bytes());
+
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Decode base32 case-insensitively, ignoring spaces, dashes and padding.
+fn base32_decode(value: &str) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    let (mut buffer, mut bits) = (0u32, 0);
+    for c in value.chars().filter(|c| !matches!(c, ' ' | '-' | '=')) {
+        let index = BASE32
+            .iter()
+            .position(|&b| char::from(b) == c.to_ascii_uppercase())
+            .ok_or_else(|| anyhow!("Invalid character '{}' in base32 secret", c))?;
+
+        buffer = (buffer << 5) | index as u32;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    ensure!(!bytes.is_empty(), "The secret is empty");
+
+    Ok(bytes)
+}
+
+/// Encode as base32 without padding, like authenticator apps do.
+fn base32_encode(bytes: &[u8]) -> String {
+    let mut encoded = String::new();
+    let (mut buffer, mut bits) = (0u32, 0);
+    for &byte in bytes {
+        buffer = (buffer << 8) | u32::from(byte);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            encoded.push(char::from(BASE32[(buffer >> bits) as usize & 0x1f]));
+        }
+        buffer &= (1 << bits) - 1;
+    }
+    if bits > 0 {
+        encoded.push(char::from(BASE32[(buffer << (5 - bits)) as usize & 0x1f]));
+    }
+
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Algorithm, Kind, Otp};
+    use anyhow::Result;
+
+    fn otp(secret: &[u8], algorithm: Algorithm, digits: u32) -> Otp {
+        Otp {
+            label: String::new(),
+            issuer: None,
+            secret: secret.to_vec(),
+            algorithm,
+            digits,
+            kind: Kind::Totp { period: 30 },
+        }
+    }
+
+    #[test]
+    fn hotp() {
+        // Test values of RFC 4226
+        let otp = otp(b"12345678901234567890", Algorithm::Sha1, 6);
+        let codes = [
+            "755224", "287082", "359152", "969429", "338314", "254676", "287922", "162583",
+            "399871", "520489",
+        ];
+        for (counter, code) in codes.iter().enumerate() {
+            assert_eq!(otp.hotp(counter as u64), *code);
+        }
+    }
+
+    #[test]
+    fn totp() {
+        // Test values of RFC 6238
+        let sha1 = otp(b"12345678901234567890", Algorithm::Sha1, 8);
+        let sha256 = otp(b"12345678901234567890123456789012", Algorithm::Sha256, 8);
+        let sha512 = otp(
+            b"1234567890123456789012345678901234567890123456789012345678901234",
+            Algorithm::Sha512,
+            8,
+        );
+
+        assert_eq!(sha1.code(59), ("94287082".to_string(), Some(1)));
+        assert_eq!(sha256.code(59).0, "46119246");
+        assert_eq!(sha512.code(59).0, "90693936");
+        assert_eq!(sha1.code(1_111_111_109).0, "07081804");
+        assert_eq!(sha256.code(1_111_111_109).0, "68084774");
+        assert_eq!(sha512.code(1_111_111_109).0, "25091201");
+        assert_eq!(sha1.code(20_000_000_000).0, "65353130");
+    }
+
+    #[test]
+    fn parse() -> Result<()> {
+        let otp = "otpauth://hotp/ACME:john%40example.com?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ&issuer=ACME&algorithm=sha256&digits=8&counter=3".parse::<Otp>()?;
+        assert_eq!(otp.label, "ACME:john%40example.com");
+        assert_eq!(otp.issuer.as_deref(), Some("ACME"));
+        assert_eq!(otp.secret, b"12345678901234567890");
+        assert_eq!(otp.algorithm, Algorithm::Sha256);
+        assert_eq!(otp.digits, 8);
+        assert_eq!(otp.kind, Kind::Hotp { counter: 3 });
+
+        // Formatting it again gives the same secret
+        assert_eq!(otp.to_string().parse::<Otp>()?, otp);
+
+        let bare = "gezd gnbv gy3t qojq gezd gnbv gy3t qojq".parse::<Otp>()?;
+        assert_eq!(bare.secret, b"12345678901234567890");
+        assert_eq!(bare.kind, Kind::Totp { period: 30 });
+        assert_eq!(
+            bare.to_string(),
+            "otpauth://totp/?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ&algorithm=SHA1&digits=6&period=30"
+        );
+
+        assert!("otpauth://hotp/x?secret=GEZDGNBV".parse::<Otp>().is_err());
+        assert!("otpauth://totp/x?secret=GEZDGNBV&digits=4"
+            .parse::<Otp>()
+            .is_err());
+        assert!("not base32!".parse::<Otp>().is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,216 @@
+use anyhow::{bail, Error, Result};
+use keybear_core::types::PublicPassword;
+use serde::{Deserialize, Serialize};
+use std::{
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// How results are written to stdout.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    /// Human readable text.
+    #[default]
+    Plain,
+    /// JSON objects, or an array of them for lists.
+    Json,
+    /// Tab separated rows without a header.
+    Tsv,
+}
+
+impl FromStr for Format {
+    type Err = Error;
+
+    fn from_str(format: &str) -> Result<Self> {
+        match format {
+            "plain" => Ok(Format::Plain),
+            "json" => Ok(Format::Json),
+            "tsv" => Ok(Format::Tsv),
+            other => bail!("Unknown output format \"{}\"", other),
+        }
+    }
+}
+
+/// A password entry in machine-readable output.
+///
+/// As TSV the columns are `id`, `name`, `email`, `website` and `password`, the last column is
+/// only there when the password is shown. Missing values are empty.
+#[derive(Debug, Serialize)]
+pub struct Entry<'a> {
+    /// Unique identifier.
+    id: &'a str,
+    /// Name of the password.
+    name: &'a str,
+    /// Associated e-mail, `null` when not set.
+    email: Option<&'a str>,
+    /// Associated website, `null` when not set.
+    website: Option<&'a str>,
+    /// The actual password, left out when it's not shown.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    password: Option<&'a str>,
+}
+
+impl<'a> Entry<'a> {
+    /// Entry with the public information of a password.
+    pub fn new(password: &'a PublicPassword) -> Self {
+        Self {
+            id: password.id(),
+            name: password.name(),
+            email: password.email(),
+            website: password.website(),
+            password: None,
+        }
+    }
+
+    /// Also show the actual password.
+    pub fn with_password(mut self, password: &'a str) -> Self {
+        self.password = Some(password);
+
+        self
+    }
+
+    /// Print the entry in the format, as labelled lines for plain text.
+    pub fn print(&self, format: Format) -> Result<()> {
+        match format {
+            Format::Plain => {
+                println!("name:     {}", self.name);
+                println!("id:       {}", self.id);
+                println!("email:    {}", self.email.unwrap_or_default());
+                println!("website:  {}", self.website.unwrap_or_default());
+                if let Some(password) = self.password {
+                    println!("password: {}", password);
+                }
+            }
+            Format::Json => print_json(self)?,
+            Format::Tsv => println!("{}", self.tsv()),
+        }
+
+        Ok(())
+    }
+
+    /// Render the entry as a TSV row.
+    pub fn tsv(&self) -> String {
+        let mut columns = vec![
+            self.id,
+            self.name,
+            self.email.unwrap_or_default(),
+            self.website.unwrap_or_default(),
+        ];
+        if let Some(password) = self.password {
+            columns.push(password);
+        }
+
+        tsv_row(&columns)
+    }
+}
+
+/// A registered device in machine-readable output.
+///
+/// As TSV the columns are `id` and `name`.
+#[derive(Debug, Serialize)]
+pub struct Device<'a> {
+    /// Unique identifier.
+    id: &'a str,
+    /// Name of the device.
+    name: &'a str,
+}
+
+impl<'a> Device<'a> {
+    /// Construct the device output.
+    pub fn new(id: &'a str, name: &'a str) -> Self {
+        Self { id, name }
+    }
+
+    /// Print the device in the format, as labelled lines for plain text.
+    pub fn print(&self, format: Format) -> Result<()> {
+        match format {
+            Format::Plain => {
+                println!("name: {}", self.name);
+                println!("id:   {}", self.id);
+            }
+            Format::Json => print_json(self)?,
+            Format::Tsv => println!("{}", tsv_row(&[self.id, self.name])),
+        }
+
+        Ok(())
+    }
+}
+
+/// Seconds since the UNIX epoch.
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+/// Describe how long ago something happened.
+pub fn ago(seconds: u64) -> String {
+    match seconds {
+        0..=59 => "just now".to_string(),
+        60..=3599 => format!("{} minutes ago", seconds / 60),
+        3600..=86399 => format!("{} hours ago", seconds / 3600),
+        _ => format!("{} days ago", seconds / 86400),
+    }
+}
+
+/// Print a value as a single line of JSON.
+pub fn print_json<T>(value: &T) -> Result<()>
+where
+    T: Serialize,
+{
+    println!("{}", serde_json::to_string(value)?);
+
+    Ok(())
+}
+
+/// Print the error as a JSON object with an `error` field on stderr.
+pub fn print_json_error(err: &Error) {
+    eprintln!(
+        "{}",
+        serde_json::json!({
+            "error": format!("{:#}", err),
+        })
+    );
+}
+
+/// Join the columns with tabs, escaping tabs, newlines and backslashes in the values.
+pub fn tsv_row(columns: &[&str]) -> String {
+    columns
+        .iter()
+        .map(|column| {
+            column
+                .replace('\\', "\\\\")
+                .replace('\t', "\\t")
+                .replace('\n', "\\n")
+                .replace('\r', "\\r")
+        })
+        .collect::<Vec<_>>()
+        .join("\t")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Entry;
+    use anyhow::Result;
+    use keybear_core::types::PublicPassword;
+
+    #[test]
+    fn entry() -> Result<()> {
+        let password = PublicPassword::new::<_, _, _, String>("1", "mail", Some("a@b.c"), None);
+
+        let entry = Entry::new(&password);
+        assert_eq!(
+            serde_json::to_string(&entry)?,
+            r#"{"id":"1","name":"mail","email":"a@b.c","website":null}"#
+        );
+        assert_eq!(entry.tsv(), "1\tmail\ta@b.c\t");
+
+        // Special characters are escaped in TSV
+        let entry = entry.with_password("tab\tnew\nline\\");
+        assert_eq!(entry.tsv(), "1\tmail\ta@b.c\t\ttab\\tnew\\nline\\\\");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,143 @@
+use anyhow::{anyhow, ensure, Result};
+use log::debug;
+use std::io::{self, IsTerminal, Read, Write};
+
+/// Read a password from the user.
+///
+/// On a terminal the password is asked twice without echoing it, otherwise it's read from the
+/// first line of stdin. When `multiline` is set everything until EOF is read.
+pub fn password(multiline: bool) -> Result<String> {
+    let password = if multiline {
+        if io::stdin().is_terminal() {
+            eprintln!("Enter the password, finish with Ctrl+D:");
+        }
+
+        debug!("Reading multi-line password from stdin");
+
+        let mut password = String::new();
+        io::stdin()
+            .read_to_string(&mut password)
+            .map_err(|err| anyhow!("Could not read password from stdin: {}", err))?;
+
+        // Remove the final newline but keep the others
+        strip_newline(&password).to_string()
+    } else if io::stdin().is_terminal() {
+        let password = hidden("Password: ")?;
+        let retyped = hidden("Retype password: ")?;
+
+        ensure!(password == retyped, "Passwords don't match");
+
+        password
+    } else {
+        debug!("Reading password from stdin");
+
+        let mut password = String::new();
+        io::stdin()
+            .read_line(&mut password)
+            .map_err(|err| anyhow!("Could not read password from stdin: {}", err))?;
+
+        strip_newline(&password).to_string()
+    };
+
+    ensure!(!password.is_empty(), "Password can't be empty");
+
+    Ok(password)
+}
+
+/// Read a secret like a passphrase from the user.
+///
+/// On a terminal it's asked without echoing it, twice when `retype` is set, otherwise it's read
+/// from the first line of stdin.
+pub fn secret(name: &str, retype: bool) -> Result<String> {
+    let secret = if io::stdin().is_terminal() {
+        let secret = hidden(&format!("{}: ", name))?;
+        if retype {
+            ensure!(
+                secret == hidden(&format!("Retype {}: ", name.to_lowercase()))?,
+                "{}s don't match",
+                name
+            );
+        }
+
+        secret
+    } else {
+        debug!("Reading {} from stdin", name.to_lowercase());
+
+        let mut secret = String::new();
+        io::stdin()
+            .read_line(&mut secret)
+            .map_err(|err| anyhow!("Could not read {} from stdin: {}", name.to_lowercase(), err))?;
+
+        strip_newline(&secret).to_string()
+    };
+
+    ensure!(!secret.is_empty(), "{} can't be empty", name);
+
+    Ok(secret)
+}
+
+/// Ask for a value on the terminal without echoing it.
+pub fn hidden(prompt: &str) -> Result<String> {
+    rpassword::read_password_from_tty(Some(prompt))
+        .map_err(|err| anyhow!("Could not read from terminal: {}", err))
+}
+
+/// Ask a yes or no question, anything but "y" or "yes" is a no.
+pub fn confirm(question: &str) -> Result<bool> {
+    eprint!("{} [y/N] ", question);
+    io::stderr().flush()?;
+
+    let mut answer = String::new();
+    io::stdin()
+        .read_line(&mut answer)
+        .map_err(|err| anyhow!("Could not read answer from stdin: {}", err))?;
+
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Whether the user can answer questions on the terminal.
+pub fn interactive() -> bool {
+    io::stdin().is_terminal() && io::stderr().is_terminal()
+}
+
+/// Let the user choose one of the options by its number, returns the index.
+pub fn choose(question: &str, options: &[String]) -> Result<usize> {
+    eprintln!("{}", question);
+    options
+        .iter()
+        .enumerate()
+        .for_each(|(index, option)| eprintln!("  {}) {}", index + 1, option));
+
+    loop {
+        eprint!("Choose 1-{}: ", options.len());
+        io::stderr().flush()?;
+
+        let mut answer = String::new();
+        let read = io::stdin()
+            .read_line(&mut answer)
+            .map_err(|err| anyhow!("Could not read answer from stdin: {}", err))?;
+        ensure!(read > 0, "No password chosen");
+
+        match answer.trim().parse::<usize>() {
+            Ok(number) if number >= 1 && number <= options.len() => return Ok(number - 1),
+            _ => eprintln!("Invalid choice \"{}\"", answer.trim()),
+        }
+    }
+}
+
+/// Remove a single trailing `\n` or `\r\n`.
+fn strip_newline(line: &str) -> &str {
+    line.strip_suffix('\n')
+        .map(|line| line.strip_suffix('\r').unwrap_or(line))
+        .unwrap_or(line)
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn strip_newline() {
+        assert_eq!(super::strip_newline("a\nb\n\n"), "a\nb\n");
+        assert_eq!(super::strip_newline("a\r\n"), "a");
+        assert_eq!(super::strip_newline("a"), "a");
+    }
+}
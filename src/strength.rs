@@ -0,0 +1,428 @@
+use crate::output;
+use chbs::word::BUILTIN_EFF_LARGE;
+use std::collections::{HashMap, HashSet};
+
+/// Most used passwords, the most used first.
+const COMMON: &[&str] = &[
+    "123456",
+    "password",
+    "123456789",
+    "12345678",
+    "12345",
+    "qwerty",
+    "1234567",
+    "111111",
+    "1234567890",
+    "123123",
+    "abc123",
+    "1234",
+    "password1",
+    "iloveyou",
+    "1q2w3e4r",
+    "000000",
+    "qwerty123",
+    "zaq12wsx",
+    "dragon",
+    "sunshine",
+    "princess",
+    "letmein",
+    "654321",
+    "monkey",
+    "1qaz2wsx",
+    "123321",
+    "qwertyuiop",
+    "superman",
+    "asdfghjkl",
+    "trustno1",
+    "welcome",
+    "admin",
+    "football",
+    "baseball",
+    "master",
+    "shadow",
+    "michael",
+    "jennifer",
+    "hunter",
+    "hunter2",
+    "starwars",
+    "whatever",
+    "freedom",
+    "passw0rd",
+    "login",
+    "access",
+    "mustang",
+    "batman",
+    "charlie",
+    "secret",
+    "summer",
+    "flower",
+    "hello",
+    "ginger",
+    "pokemon",
+    "computer",
+    "cheese",
+    "internet",
+    "maggie",
+    "buster",
+    "soccer",
+    "hockey",
+    "killer",
+    "george",
+    "jordan",
+    "harley",
+    "ranger",
+    "thomas",
+    "tigger",
+    "robert",
+    "daniel",
+    "andrew",
+    "joshua",
+    "matthew",
+    "pepper",
+    "ashley",
+    "nicole",
+    "jessica",
+    "lovely",
+    "angel",
+    "chocolate",
+    "cookie",
+    "banana",
+    "orange",
+    "purple",
+    "samsung",
+    "google",
+    "changeme",
+    "default",
+    "root",
+    "toor",
+    "test",
+    "guest",
+    "qazwsx",
+    "zxcvbnm",
+    "asdf",
+    "abcdef",
+    "michelle",
+    "liverpool",
+    "chelsea",
+    "arsenal",
+    "yankees",
+    "matrix",
+    "merlin",
+    "mercedes",
+    "ferrari",
+    "corvette",
+    "keybear",
+];
+
+/// Rows of a QWERTY keyboard, typing along them is a common pattern.
+const KEYBOARD_ROWS: &[&str] = &[
+    "`1234567890-=",
+    "~!@#$%^&*()_+",
+    "qwertyuiop[]\\",
+    "asdfghjkl;'",
+    "zxcvbnm,./",
+];
+
+/// Characters that are used instead of letters.
+const L33T: &[(char, char)] = &[
+    ('4', 'a'),
+    ('@', 'a'),
+    ('8', 'b'),
+    ('3', 'e'),
+    ('6', 'g'),
+    ('9', 'g'),
+    ('1', 'i'),
+    ('!', 'i'),
+    ('0', 'o'),
+    ('5', 's'),
+    ('$', 's'),
+    ('7', 't'),
+    ('+', 't'),
+    ('2', 'z'),
+];
+
+/// Guesses per character of parts that don't match a pattern.
+const BRUTEFORCE_CARDINALITY: f64 = 10.0;
+/// Average length of a year of the Gregorian calendar in seconds.
+const YEAR: u64 = 31_556_952;
+/// Only this many characters are analyzed, longer passwords are strong enough anyway.
+const MAX_LENGTH: usize = 64;
+
+/// How hard a password is to guess.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Estimate {
+    /// Estimated guesses needed to crack it.
+    pub guesses: f64,
+    /// 0 for too guessable up to 4 for very unguessable, the same scale as zxcvbn.
+    pub score: u8,
+}
+
+/// Estimates the strength of passwords like zxcvbn.
+///
+/// The password is split into the parts that are cheapest to guess, like common passwords,
+/// dictionary words, keyboard patterns, sequences, repeats and years. The guesses of the
+/// parts are multiplied, the rest is brute-forced.
+#[derive(Debug)]
+pub struct Estimator {
+    /// Common passwords with their rank.
+    common: HashMap<&'static str, usize>,
+    /// Words of the EFF wordlist.
+    words: HashSet<&'static str>,
+    /// Years are guessed starting from this one, the current year.
+    reference_year: i32,
+}
+
+impl Estimator {
+    /// Load the dictionaries.
+    pub fn new() -> Self {
+        Self {
+            common: COMMON
+                .iter()
+                .enumerate()
+                .map(|(index, password)| (*password, index + 1))
+                .collect(),
+            words: BUILTIN_EFF_LARGE
+                .lines()
+                .map(str::trim)
+                .filter(|word| !word.is_empty())
+                .collect(),
+            // Can be off by a day around new year, which doesn't matter for guessing
+            reference_year: 1970 + (output::now() / YEAR) as i32,
+        }
+    }
+
+    /// Estimate the strength of the password.
+    pub fn estimate(&self, password: &str) -> Estimate {
+        let guesses = self.guesses(&password.chars().take(MAX_LENGTH).collect::<Vec<_>>());
+
+        let score = match guesses {
+            guesses if guesses < 1e3 + 5.0 => 0,
+            guesses if guesses < 1e6 + 5.0 => 1,
+            guesses if guesses < 1e8 + 5.0 => 2,
+            guesses if guesses < 1e10 + 5.0 => 3,
+            _ => 4,
+        };
+
+        Estimate { guesses, score }
+    }
+
+    /// The fewest guesses of any way to split the password into patterns.
+    fn guesses(&self, password: &[char]) -> f64 {
+        let length = password.len();
+        if length == 0 {
+            return 1.0;
+        }
+
+        // The cheapest guesses of the first k characters split into l parts
+        let mut optimal = vec![vec![f64::INFINITY; length + 1]; length + 1];
+        optimal[0][0] = 1.0;
+        for end in 1..=length {
+            for start in 0..end {
+                let guesses = self.part(&password[start..end]);
+                for parts in 1..=end {
+                    let previous = optimal[start][parts - 1];
+                    if previous.is_finite() && previous * guesses < optimal[end][parts] {
+                        optimal[end][parts] = previous * guesses;
+                    }
+                }
+            }
+        }
+
+        // More parts can be ordered in more ways
+        (1..=length)
+            .filter(|&parts| optimal[length][parts].is_finite())
+            .map(|parts| {
+                factorial(parts) * optimal[length][parts] + 10_000f64.powi(parts as i32 - 1)
+            })
+            .fold(f64::INFINITY, f64::min)
+    }
+
+    /// The guesses of the cheapest pattern that matches the whole part.
+    fn part(&self, part: &[char]) -> f64 {
+        let mut guesses = BRUTEFORCE_CARDINALITY
+            .powi(part.len() as i32)
+            .max(BRUTEFORCE_CARDINALITY + 1.0);
+        if part.len() < 3 {
+            return guesses;
+        }
+
+        if let Some(dictionary) = self.dictionary(part) {
+            guesses = guesses.min(dictionary);
+        }
+        if let Some(repeat) = self.repeat(part) {
+            guesses = guesses.min(repeat);
+        }
+        if let Some(sequence) = sequence(part) {
+            guesses = guesses.min(sequence);
+        }
+        if let Some(spatial) = spatial(part) {
+            guesses = guesses.min(spatial);
+        }
+        if let Some(year) = self.year(part) {
+            guesses = guesses.min(year);
+        }
+
+        guesses
+    }
+
+    /// A common password or word, also reversed or with letters replaced by look-alikes.
+    fn dictionary(&self, part: &[char]) -> Option<f64> {
+        let lowercase = part
+            .iter()
+            .flat_map(|c| c.to_lowercase())
+            .collect::<String>();
+        let substituted = lowercase
+            .chars()
+            .filter(|c| L33T.iter().any(|(from, _)| from == c))
+            .count();
+        let unl33ted = lowercase
+            .chars()
+            .map(|c| {
+                L33T.iter()
+                    .find(|(from, _)| *from == c)
+                    .map_or(c, |(_, to)| *to)
+            })
+            .collect::<String>();
+
+        let rank = |word: &str| {
+            self.common
+                .get(word)
+                .map(|rank| *rank as f64)
+                .or_else(|| self.words.contains(word).then_some(self.words.len() as f64))
+        };
+
+        let mut candidates = vec![(rank(&lowercase), 1.0)];
+        if substituted > 0 {
+            candidates.push((rank(&unl33ted), 2f64.powi(substituted as i32)));
+        }
+        let reversed = lowercase.chars().rev().collect::<String>();
+        candidates.push((rank(&reversed), 2.0));
+
+        candidates
+            .into_iter()
+            .filter_map(|(rank, variations)| rank.map(|rank| rank * variations))
+            .fold(None, |min: Option<f64>, guesses| {
+                Some(min.map_or(guesses, |min| min.min(guesses)))
+            })
+            .map(|guesses| guesses * uppercase_variations(part))
+    }
+
+    /// The same character or block repeated.
+    fn repeat(&self, part: &[char]) -> Option<f64> {
+        (1..=part.len() / 2)
+            .filter(|&unit| part.len().is_multiple_of(unit))
+            .find(|&unit| part.chunks(unit).all(|chunk| chunk == &part[..unit]))
+            .map(|unit| self.guesses(&part[..unit]) * (part.len() / unit) as f64)
+    }
+
+    /// A year of the last and this century, recent years are guessed first.
+    fn year(&self, part: &[char]) -> Option<f64> {
+        if part.len() != 4 || !part.iter().all(char::is_ascii_digit) {
+            return None;
+        }
+
+        let year = part.iter().collect::<String>().parse::<i32>().ok()?;
+        (1900..=2099)
+            .contains(&year)
+            .then(|| f64::from((year - self.reference_year).abs().max(20)))
+    }
+}
+
+/// Letters or digits in alphabetical order, forwards or backwards.
+fn sequence(part: &[char]) -> Option<f64> {
+    let delta = part[1] as i64 - part[0] as i64;
+    let same_class = |c: &char| {
+        (c.is_ascii_lowercase() && part[0].is_ascii_lowercase())
+            || (c.is_ascii_uppercase() && part[0].is_ascii_uppercase())
+            || (c.is_ascii_digit() && part[0].is_ascii_digit())
+    };
+    if delta.abs() != 1
+        || !part.iter().all(same_class)
+        || !part
+            .windows(2)
+            .all(|pair| pair[1] as i64 - pair[0] as i64 == delta)
+    {
+        return None;
+    }
+
+    // Obvious starting points are guessed first
+    let base = if matches!(part[0], 'a' | 'A' | 'z' | 'Z' | '0' | '1' | '9') {
+        4.0
+    } else if part[0].is_ascii_digit() {
+        10.0
+    } else {
+        26.0
+    };
+    let descending = if delta < 0 { 2.0 } else { 1.0 };
+
+    Some(base * part.len() as f64 * descending)
+}
+
+/// Keys next to each other on a keyboard row, forwards or backwards.
+fn spatial(part: &[char]) -> Option<f64> {
+    let lowercase = part
+        .iter()
+        .flat_map(|c| c.to_lowercase())
+        .collect::<String>();
+    let reversed = lowercase.chars().rev().collect::<String>();
+
+    KEYBOARD_ROWS
+        .iter()
+        .any(|row| row.contains(&lowercase) || row.contains(&reversed))
+        .then(|| 100.0 * part.len() as f64 * uppercase_variations(part))
+}
+
+/// Ways the letters can be capitalized, starting with the common ones.
+fn uppercase_variations(part: &[char]) -> f64 {
+    let upper = part.iter().filter(|c| c.is_uppercase()).count();
+    let lower = part.iter().filter(|c| c.is_lowercase()).count();
+
+    let first_only = upper == 1 && part[0].is_uppercase();
+    let last_only = upper == 1 && part[part.len() - 1].is_uppercase();
+    if upper == 0 || lower == 0 || first_only || last_only {
+        return if upper == 0 { 1.0 } else { 2.0 };
+    }
+
+    (1..=upper.min(lower))
+        .map(|k| binomial(upper + lower, k))
+        .sum()
+}
+
+/// `n!` as a float, so it doesn't overflow.
+fn factorial(n: usize) -> f64 {
+    (2..=n).map(|i| i as f64).product()
+}
+
+/// `n` choose `k` as a float.
+fn binomial(n: usize, k: usize) -> f64 {
+    (1..=k).map(|i| (n + 1 - i) as f64 / i as f64).product()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Estimator;
+
+    #[test]
+    fn estimate() {
+        let estimator = Estimator::new();
+        let score = |password: &str| estimator.estimate(password).score;
+
+        // Common passwords and simple patterns
+        assert_eq!(score("password"), 0);
+        assert_eq!(score("P4ssw0rd"), 0);
+        assert_eq!(score("qwerty123"), 0);
+        assert_eq!(score("aaaaaaaaaaaa"), 0);
+        assert_eq!(score("abcdefgh"), 0);
+        assert!(score("Summer2024") <= 2);
+        assert_eq!(score(&estimator.reference_year.to_string()), 0);
+
+        // Random characters and pass-phrases
+        assert_eq!(score("x7#Kp9!qLz2$"), 4);
+        assert_eq!(score("correct horse battery staple"), 4);
+
+        // Longer is harder
+        assert!(
+            estimator.estimate("hunter2").guesses < estimator.estimate("hunter2hunter").guesses
+        );
+    }
+}
@@ -0,0 +1,244 @@
+use crate::{config::Config, file::create_private_file, output::now};
+use anyhow::{anyhow, ensure, Result};
+use chacha20poly1305::{
+    aead::{Aead, NewAead},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use hkdf::Hkdf;
+use log::{debug, warn};
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::{
+    cmp::Reverse,
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+use x25519_dalek::{PublicKey, StaticSecret};
+
+/// Extension of the files in the trash.
+const EXTENSION: &str = "trash";
+/// Size of the nonce at the start of every file.
+const NONCE_SIZE: usize = 12;
+/// Seconds in a day.
+const DAY: u64 = 24 * 60 * 60;
+/// Context of the derived key, so it differs from any other key made from the secret key.
+const KEY_INFO: &[u8] = b"keybear trash v1";
+
+/// A removed password.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Trashed {
+    /// ID the password had on the server.
+    pub id: String,
+    /// Name of the password.
+    pub name: String,
+    /// Associated e-mail.
+    pub email: Option<String>,
+    /// Associated website.
+    pub website: Option<String>,
+    /// The actual password.
+    pub password: String,
+    /// When the password was removed, in seconds since the UNIX epoch.
+    pub deleted: u64,
+}
+
+/// Local directory with removed passwords, every password is encrypted in its own file.
+///
+/// The files are encrypted with a key derived from the secret key of the device, so they can
+/// only be read on this device.
+pub struct Trash {
+    /// Directory containing the files.
+    dir: PathBuf,
+    /// Key to encrypt and decrypt the files with.
+    key: Key,
+    /// Seconds after which files are deleted, 0 to keep them forever.
+    retention: u64,
+}
+
+impl Trash {
+    /// Open the trash from the configuration, removing expired passwords.
+    pub fn open(config: &Config) -> Result<Self> {
+        let secret_key = config.secret_key()?;
+
+        let trash = Self {
+            dir: config.trash_path(),
+            key: key(&secret_key),
+            retention: config.trash().retention_days() * DAY,
+        };
+        trash.expire()?;
+
+        Ok(trash)
+    }
+
+    /// Put a removed password in the trash, returns the path of its file.
+    pub fn put(&self, trashed: &Trashed) -> Result<PathBuf> {
+        debug!("Moving password \"{}\" to the trash", trashed.name);
+
+        create_private_dir(&self.dir)?;
+
+        // Every file gets a random nonce that's stored in front of the encrypted bytes
+        let mut nonce = [0; NONCE_SIZE];
+        OsRng.fill_bytes(&mut nonce);
+        let encrypted = ChaCha20Poly1305::new(&self.key)
+            .encrypt(&Nonce::from(nonce), serde_json::to_vec(trashed)?.as_slice())
+            .map_err(|_| anyhow!("Could not encrypt password"))?;
+
+        let path = self
+            .dir
+            .join(format!("{}-{}.{}", trashed.deleted, trashed.id, EXTENSION));
+        let mut file = create_private_file(&path)
+            .map_err(|err| anyhow!("Could not create trash file {:?}: {}", path, err))?;
+        file.write_all(&nonce)?;
+        file.write_all(&encrypted)?;
+
+        Ok(path)
+    }
+
+    /// All passwords in the trash, the most recently removed first.
+    ///
+    /// Files that can't be decrypted are skipped with a warning.
+    pub fn list(&self) -> Result<Vec<(PathBuf, Trashed)>> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut entries = fs::read_dir(&self.dir)
+            .map_err(|err| anyhow!("Could not read trash directory {:?}: {}", self.dir, err))?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == EXTENSION))
+            .filter_map(|path| match self.read(&path) {
+                Ok(trashed) => Some((path, trashed)),
+                Err(err) => {
+                    warn!("Skipping trash file {:?}: {}", path, err);
+
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+        entries.sort_by_key(|(_, trashed)| Reverse(trashed.deleted));
+
+        Ok(entries)
+    }
+
+    /// Delete a file from the trash for good.
+    pub fn delete(&self, path: &Path) -> Result<()> {
+        fs::remove_file(path)
+            .map_err(|err| anyhow!("Could not delete trash file {:?}: {}", path, err))
+    }
+
+    /// Decrypt a file in the trash.
+    fn read(&self, path: &Path) -> Result<Trashed> {
+        let bytes = fs::read(path)?;
+        ensure!(bytes.len() > NONCE_SIZE, "File is too small");
+
+        let (nonce, encrypted) = bytes.split_at(NONCE_SIZE);
+        let mut nonce_bytes = [0; NONCE_SIZE];
+        nonce_bytes.copy_from_slice(nonce);
+
+        let json = ChaCha20Poly1305::new(&self.key)
+            .decrypt(&Nonce::from(nonce_bytes), encrypted)
+            .map_err(|_| anyhow!("Could not decrypt it, it's from another device or damaged"))?;
+
+        Ok(serde_json::from_slice(&json)?)
+    }
+
+    /// Delete the passwords that are older than the retention period.
+    fn expire(&self) -> Result<()> {
+        if self.retention == 0 {
+            return Ok(());
+        }
+
+        let now = now();
+        self.list()?
+            .into_iter()
+            .filter(|(_, trashed)| now.saturating_sub(trashed.deleted) > self.retention)
+            .try_for_each(|(path, trashed)| {
+                debug!("Password \"{}\" expired from the trash", trashed.name);
+
+                self.delete(&path)
+            })
+    }
+}
+
+/// Derive the key of the trash from the secret key of the device.
+fn key(secret_key: &StaticSecret) -> Key {
+    // Agreeing on a key with our own public key gives a secret only this device knows
+    let shared = secret_key.diffie_hellman(&PublicKey::from(secret_key));
+
+    let mut key = Key::default();
+    Hkdf::<Sha256>::new(None, shared.as_bytes())
+        .expand(KEY_INFO, &mut key)
+        .expect("HKDF-SHA256 can derive 32 bytes");
+
+    key
+}
+
+/// Create a directory only the user can access.
+fn create_private_dir(dir: &Path) -> Result<()> {
+    fs::create_dir_all(dir)
+        .map_err(|err| anyhow!("Could not create trash directory {:?}: {}", dir, err))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(dir, fs::Permissions::from_mode(0o700))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Trash, Trashed, DAY};
+    use crate::output::now;
+    use anyhow::Result;
+    use keybear_core::crypto::StaticSecretExt;
+    use x25519_dalek::StaticSecret;
+
+    fn trash(dir: &std::path::Path, secret_key: &StaticSecret) -> Trash {
+        Trash {
+            dir: dir.to_path_buf(),
+            key: super::key(secret_key),
+            retention: DAY,
+        }
+    }
+
+    #[test]
+    fn roundtrip() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let secret_key = StaticSecret::new_with_os_rand();
+        let trash = trash(dir.path(), &secret_key);
+
+        let trashed = Trashed {
+            id: "1".to_string(),
+            name: "mail".to_string(),
+            email: Some("a@b.c".to_string()),
+            website: None,
+            password: "hunter2".to_string(),
+            deleted: now(),
+        };
+        trash.put(&trashed)?;
+
+        // The password isn't stored in plain text
+        let (path, read) = trash.list()?.remove(0);
+        assert_eq!(read, trashed);
+        assert!(!String::from_utf8_lossy(&std::fs::read(&path)?).contains("hunter2"));
+
+        // Another device can't read it
+        let other = self::trash(dir.path(), &StaticSecret::new_with_os_rand());
+        assert!(other.list()?.is_empty());
+
+        // Old passwords are removed
+        trash.put(&Trashed {
+            id: "2".to_string(),
+            deleted: now() - 2 * DAY,
+            ..trashed
+        })?;
+        assert_eq!(trash.list()?.len(), 2);
+        trash.expire()?;
+        assert_eq!(trash.list()?.len(), 1);
+
+        Ok(())
+    }
+}
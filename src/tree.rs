@@ -0,0 +1,137 @@
+use std::collections::BTreeMap;
+
+/// Separator between the folders in a password name.
+pub const SEPARATOR: char = '/';
+
+/// Remove the separators around a folder name.
+pub fn folder(name: &str) -> &str {
+    name.trim_matches(SEPARATOR)
+}
+
+/// Whether the password name is the folder itself or inside it.
+///
+/// An empty folder contains everything.
+pub fn contains(folder: &str, name: &str) -> bool {
+    let folder = self::folder(folder);
+    let name = self::folder(name);
+
+    folder.is_empty()
+        || name == folder
+        || (name.starts_with(folder) && name[folder.len()..].starts_with(SEPARATOR))
+}
+
+/// Folders and passwords sorted by name.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Tree {
+    /// Sub-folders and passwords, a password can also be a folder when there are passwords
+    /// with its name as prefix.
+    children: BTreeMap<String, Tree>,
+}
+
+impl Tree {
+    /// Build a tree from the password names, relative to the folder.
+    ///
+    /// Names outside of the folder are ignored.
+    pub fn new<'a, I>(folder: &str, names: I) -> Self
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let prefix = self::folder(folder);
+
+        let mut tree = Self::default();
+        names
+            .into_iter()
+            .filter(|name| contains(prefix, name))
+            .for_each(|name| {
+                // Only take the part after the folder
+                let relative = self::folder(name)[prefix.len()..].trim_start_matches(SEPARATOR);
+
+                relative
+                    .split(SEPARATOR)
+                    // Ignore double separators
+                    .filter(|part| !part.is_empty())
+                    .fold(&mut tree, |node, part| {
+                        node.children.entry(part.to_string()).or_default()
+                    });
+            });
+
+        tree
+    }
+
+    /// Render the tree with box-drawing characters below a root line.
+    pub fn render(&self, root: &str) -> String {
+        let mut output = format!("{}\n", root);
+        self.render_children("", &mut output);
+
+        output
+    }
+
+    /// Render the children with the indentation of the parents.
+    fn render_children(&self, indent: &str, output: &mut String) {
+        let last = self.children.len().saturating_sub(1);
+
+        self.children
+            .iter()
+            .enumerate()
+            .for_each(|(index, (name, child))| {
+                let (branch, next_indent) = if index == last {
+                    ("└── ", "    ")
+                } else {
+                    ("├── ", "│   ")
+                };
+
+                output.push_str(&format!("{}{}{}\n", indent, branch, name));
+                child.render_children(&format!("{}{}", indent, next_indent), output);
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Tree;
+
+    #[test]
+    fn contains() {
+        assert!(super::contains("", "mail"));
+        assert!(super::contains("work", "work/aws/prod"));
+        assert!(super::contains("work/", "work/aws/prod"));
+        assert!(super::contains("work/aws", "work/aws"));
+
+        assert!(!super::contains("work", "workshop"));
+        assert!(!super::contains("work/aws", "work"));
+    }
+
+    #[test]
+    fn render() {
+        let names = [
+            "work/aws/prod-root",
+            "mail",
+            "work/aws/dev",
+            "work/github",
+            "workshop",
+        ];
+
+        assert_eq!(
+            Tree::new("", names.iter().copied()).render("Passwords"),
+            "Passwords
+├── mail
+├── work
+│   ├── aws
+│   │   ├── dev
+│   │   └── prod-root
+│   └── github
+└── workshop
+"
+        );
+
+        assert_eq!(
+            Tree::new("work/aws/", names.iter().copied()).render("work/aws"),
+            "work/aws
+├── dev
+└── prod-root
+"
+        );
+
+        assert_eq!(Tree::new("other", names.iter().copied()), Tree::default());
+    }
+}
@@ -0,0 +1,69 @@
+use anyhow::{anyhow, bail, ensure, Result};
+use reqwest::Url;
+
+/// Check whether the text looks like an e-mail address.
+pub fn email(email: &str) -> Result<()> {
+    let (local, domain) = email
+        .rsplit_once('@')
+        .ok_or_else(|| anyhow!("E-mail address \"{}\" doesn't contain an '@'", email))?;
+
+    ensure!(
+        !email.chars().any(char::is_whitespace),
+        "E-mail address \"{}\" can't contain whitespace",
+        email
+    );
+    ensure!(
+        !local.is_empty(),
+        "E-mail address \"{}\" is missing the part before the '@'",
+        email
+    );
+    ensure!(
+        domain.split('.').count() >= 2 && domain.split('.').all(|part| !part.is_empty()),
+        "E-mail address \"{}\" has an invalid domain",
+        email
+    );
+
+    Ok(())
+}
+
+/// Check whether the text is a valid website URL, the scheme can be omitted.
+pub fn website(website: &str) -> Result<()> {
+    // Allow websites like "example.com"
+    let url = if website.contains("://") {
+        Url::parse(website)
+    } else {
+        Url::parse(&format!("https://{}", website))
+    }
+    .map_err(|err| anyhow!("Website \"{}\" is not a valid URL: {}", website, err))?;
+
+    match url.host_str() {
+        Some(host) if !host.is_empty() => Ok(()),
+        _ => bail!("Website \"{}\" doesn't have a host", website),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn email() {
+        assert!(super::email("user@example.com").is_ok());
+        assert!(super::email("first.last+tag@mail.example.org").is_ok());
+
+        assert!(super::email("example.com").is_err());
+        assert!(super::email("@example.com").is_err());
+        assert!(super::email("user@localhost").is_err());
+        assert!(super::email("user@example..com").is_err());
+        assert!(super::email("us er@example.com").is_err());
+    }
+
+    #[test]
+    fn website() {
+        assert!(super::website("example.com").is_ok());
+        assert!(super::website("https://example.com/login?next=/").is_ok());
+        assert!(super::website("http://localhost:8080").is_ok());
+
+        assert!(super::website("").is_err());
+        assert!(super::website("https://").is_err());
+        assert!(super::website("exa mple.com").is_err());
+    }
+}
@@ -0,0 +1,253 @@
+use crate::{
+    net::{self, Client},
+    prompt,
+};
+use anyhow::{anyhow, bail, Result};
+use keybear_core::{
+    route::v1,
+    types::{PasswordRequest, PasswordResponse, PublicPassword, RegisterPasswordRequest},
+};
+use log::debug;
+
+/// The information stored next to a password.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Metadata {
+    /// Associated e-mail.
+    pub email: Option<String>,
+    /// Associated website.
+    pub website: Option<String>,
+}
+
+impl Metadata {
+    /// Fill the fields that aren't set with the ones of an existing password.
+    pub fn or_from(self, password: &PublicPassword) -> Self {
+        Self {
+            email: self.email.or_else(|| password.email().map(String::from)),
+            website: self
+                .website
+                .or_else(|| password.website().map(String::from)),
+        }
+    }
+
+    /// Build the request to register a password with this metadata.
+    pub fn request(&self, name: &str, password: &str) -> RegisterPasswordRequest {
+        RegisterPasswordRequest::new(
+            name,
+            password,
+            self.email.as_deref(),
+            self.website.as_deref(),
+        )
+    }
+}
+
+/// Which existing password a command works on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Selection<'a> {
+    /// Name of the password.
+    pub name: &'a str,
+    /// ID of the password, needed when several have the same name.
+    pub id: Option<&'a str>,
+}
+
+impl<'a> Selection<'a> {
+    /// Select the password by its name and optional ID.
+    pub fn new(name: &'a str, id: Option<&'a str>) -> Self {
+        Self { name, id }
+    }
+
+    /// Whether the password is selected.
+    pub fn matches(&self, password: &PublicPassword) -> bool {
+        password.name() == self.name && self.id.is_none_or(|id| password.id() == id)
+    }
+
+    /// Pick the selected password from the list.
+    ///
+    /// When several passwords match the user can choose one if `interactive` is set, otherwise
+    /// it's an error listing them.
+    pub fn pick<'p>(
+        &self,
+        passwords: &'p [PublicPassword],
+        interactive: bool,
+    ) -> Result<&'p PublicPassword> {
+        let candidates = passwords
+            .iter()
+            .filter(|password| self.matches(password))
+            .collect::<Vec<_>>();
+
+        match (candidates.as_slice(), self.id) {
+            ([], None) => bail!("Password with name \"{}\" does not exist", self.name),
+            ([], Some(id)) => bail!(
+                "Password with name \"{}\" and ID \"{}\" does not exist",
+                self.name,
+                id
+            ),
+            ([password], _) => Ok(password),
+            (candidates, _) if interactive => {
+                let index = prompt::choose(
+                    &format!(
+                        "Several passwords have the name \"{}\", which one?",
+                        self.name
+                    ),
+                    &candidates
+                        .iter()
+                        .map(|password| describe(password))
+                        .collect::<Vec<_>>(),
+                )?;
+
+                Ok(candidates[index])
+            }
+            (candidates, _) => bail!(
+                "Several passwords have the name \"{}\", select one with --id:\n{}",
+                self.name,
+                candidates
+                    .iter()
+                    .map(|password| format!("  {}", describe(password)))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            ),
+        }
+    }
+}
+
+/// Describe a password by its ID and metadata to tell it apart from others with the same name.
+fn describe(password: &PublicPassword) -> String {
+    format!(
+        "ID: {}, e-mail: {}, website: {}",
+        password.id(),
+        password.email().unwrap_or("-"),
+        password.website().unwrap_or("-")
+    )
+}
+
+/// Request the public information of all passwords.
+pub async fn passwords(client: &Client<'_>) -> Result<Vec<PublicPassword>> {
+    debug!("Requesting all passwords");
+
+    client.get::<(), _, _>(v1::PASSWORD, None).await
+}
+
+/// Find the public information of the selected password.
+///
+/// On a terminal the user is asked to choose when the name is ambiguous.
+pub async fn find(client: &Client<'_>, selection: &Selection<'_>) -> Result<PublicPassword> {
+    Ok(selection
+        .pick(&passwords(client).await?, prompt::interactive())?
+        .clone())
+}
+
+/// Whether another password has the same name.
+pub fn ambiguous(passwords: &[PublicPassword], password: &PublicPassword) -> bool {
+    passwords
+        .iter()
+        .any(|other| other.name() == password.name() && other.id() != password.id())
+}
+
+/// Request the actual password of an entry.
+///
+/// The server looks the password up by its name, so it can't tell passwords with the same name
+/// apart. To never return the secret of another entry it's an error when one of `passwords`, all
+/// passwords on the server, has the same name.
+pub async fn secret(
+    client: &Client<'_>,
+    passwords: &[PublicPassword],
+    password: &PublicPassword,
+) -> Result<PasswordResponse> {
+    if ambiguous(passwords, password) {
+        bail!(
+            "Several passwords have the name \"{}\" and the server can only return them by name, remove the others with --id first",
+            password.name()
+        );
+    }
+
+    debug!("Requesting password with name \"{}\"", password.name());
+
+    // Build the request object
+    let request = PasswordRequest::from_name(password.name());
+
+    client
+        .post(net::route(v1::PASSWORD, password.name())?, &request)
+        .await
+}
+
+/// Register a new password.
+pub async fn register(
+    client: &Client<'_>,
+    request: &RegisterPasswordRequest,
+) -> Result<PublicPassword> {
+    debug!("Registering password with name \"{}\"", request.name());
+
+    client.post(v1::PASSWORD, request).await
+}
+
+/// Remove a password.
+pub async fn remove(client: &Client<'_>, password: &PublicPassword) -> Result<()> {
+    debug!("Removing password with ID \"{}\"", password.id());
+
+    // Build the request object
+    let request = PasswordRequest::from_id(password.id());
+
+    client.delete(v1::PASSWORD, &request).await
+}
+
+/// Replace an existing password with a new version.
+///
+/// The server doesn't have a route to update a password, so the new version is registered
+/// before the old one is removed. When something fails halfway the old version is kept.
+pub async fn replace(
+    client: &Client<'_>,
+    old: &PublicPassword,
+    request: &RegisterPasswordRequest,
+) -> Result<PublicPassword> {
+    let new = register(client, request).await?;
+
+    remove(client, old).await.map_err(|err| {
+        anyhow!(
+            "Password \"{}\" was saved with ID {} but the old version with ID {} could not be removed: {}",
+            request.name(),
+            new.id(),
+            old.id(),
+            err
+        )
+    })?;
+
+    Ok(new)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Selection;
+    use keybear_core::types::PublicPassword;
+
+    #[test]
+    fn pick() {
+        let passwords = vec![
+            PublicPassword::new::<_, _, _, String>("1", "mail", Some("a@b.c"), None),
+            PublicPassword::new::<_, _, String, String>("2", "github", None, None),
+            PublicPassword::new::<_, _, _, String>("3", "mail", Some("d@e.f"), None),
+        ];
+
+        let picked = Selection::new("github", None)
+            .pick(&passwords, false)
+            .unwrap();
+        assert_eq!(picked.id(), "2");
+        let picked = Selection::new("mail", Some("3"))
+            .pick(&passwords, false)
+            .unwrap();
+        assert_eq!(picked.id(), "3");
+
+        // The candidates are listed when the name is ambiguous
+        let err = Selection::new("mail", None)
+            .pick(&passwords, false)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("ID: 1, e-mail: a@b.c, website: -"));
+        assert!(err.contains("ID: 3, e-mail: d@e.f, website: -"));
+
+        assert!(Selection::new("mail", Some("2"))
+            .pick(&passwords, false)
+            .is_err());
+        assert!(Selection::new("other", None)
+            .pick(&passwords, false)
+            .is_err());
+    }
+}
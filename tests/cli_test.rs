@@ -21,7 +21,48 @@ fn register() {
     let mut cmd = Command::cargo_bin("kb").unwrap();
 
     // Only call the help command
-    cmd.args(&["register", "-c", file_path.to_str().unwrap(), "-h"])
+    cmd.args(["register", "-c", file_path.to_str().unwrap(), "-h"])
         .assert()
         .success();
 }
+
+#[test]
+fn insert_multiline_with_password() {
+    // A password can't be passed both as an argument and through stdin
+    Command::cargo_bin("kb")
+        .unwrap()
+        .args(["insert", "name", "password", "--multiline"])
+        .assert()
+        .code(2);
+}
+
+#[cfg(unix)]
+#[test]
+fn export_world_readable() {
+    use std::{fs, os::unix::fs::PermissionsExt};
+
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let config_path = tmp_dir.path().join("config.toml");
+    fs::write(&config_path, "name = \"client\"\nurl = \"test.onion\"\n").unwrap();
+
+    // Plaintext passwords shouldn't end up in a file everyone can read
+    let export_path = tmp_dir.path().join("export.json");
+    fs::write(&export_path, "").unwrap();
+    fs::set_permissions(&export_path, fs::Permissions::from_mode(0o644)).unwrap();
+
+    let output = Command::cargo_bin("kb")
+        .unwrap()
+        .args([
+            "export",
+            "-c",
+            config_path.to_str().unwrap(),
+            "--as",
+            "json",
+            "-o",
+        ])
+        .arg(&export_path)
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--i-know-this-is-plaintext"));
+}